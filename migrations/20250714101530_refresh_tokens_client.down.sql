@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE refresh_tokens
+    DROP COLUMN IF EXISTS ip_address,
+    DROP COLUMN IF EXISTS user_agent;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE refresh_tokens
+    ADD COLUMN user_agent TEXT,
+    ADD COLUMN ip_address VARCHAR(45);
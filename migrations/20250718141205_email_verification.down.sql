@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS user_tokens_user_id_idx;
+
+DROP TABLE IF EXISTS user_tokens;
+
+DROP TYPE IF EXISTS user_token_kind;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS email_verified_at;
@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+DELETE FROM user_tokens WHERE kind = 'password_reset';
+
+ALTER TYPE user_token_kind RENAME TO user_token_kind_old;
+
+CREATE TYPE user_token_kind AS ENUM ('email_verification');
+
+ALTER TABLE user_tokens
+    ALTER COLUMN kind TYPE user_token_kind USING kind::text::user_token_kind;
+
+DROP TYPE IF EXISTS user_token_kind_old;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TYPE user_token_kind ADD VALUE IF NOT EXISTS 'password_reset';
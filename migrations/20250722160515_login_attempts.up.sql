@@ -0,0 +1,8 @@
+-- Add up migration script here
+
+CREATE TABLE login_attempts (
+    key VARCHAR(320) NOT NULL PRIMARY KEY,
+    failures INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    last_failure_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,22 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS mfa_policies;
+
+DROP INDEX IF EXISTS mfa_recovery_codes_user_id_idx;
+
+DROP TABLE IF EXISTS mfa_recovery_codes;
+
+DELETE FROM user_tokens WHERE kind = 'mfa_pending';
+
+ALTER TYPE user_token_kind RENAME TO user_token_kind_old;
+
+CREATE TYPE user_token_kind AS ENUM ('email_verification', 'password_reset');
+
+ALTER TABLE user_tokens
+    ALTER COLUMN kind TYPE user_token_kind USING kind::text::user_token_kind;
+
+DROP TYPE IF EXISTS user_token_kind_old;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS totp_enabled_at,
+    DROP COLUMN IF EXISTS totp_secret;
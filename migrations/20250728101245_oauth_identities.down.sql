@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS oauth_states;
+
+DROP INDEX IF EXISTS oauth_identities_user_id_idx;
+
+DROP TABLE IF EXISTS oauth_identities;
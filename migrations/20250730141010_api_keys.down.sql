@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS api_keys_user_id_idx;
+
+DROP TABLE IF EXISTS api_keys;
@@ -0,0 +1,23 @@
+-- Add down migration script here
+
+CREATE TYPE user_role AS ENUM ('admin', 'user');
+
+DELETE FROM mfa_policies WHERE role NOT IN ('admin', 'user');
+
+ALTER TABLE mfa_policies
+    DROP CONSTRAINT IF EXISTS mfa_policies_role_fkey,
+    ALTER COLUMN role TYPE user_role USING role::user_role;
+
+UPDATE users SET role = 'user' WHERE role NOT IN ('admin', 'user');
+
+ALTER TABLE users
+    DROP CONSTRAINT IF EXISTS users_role_fkey,
+    ALTER COLUMN role DROP DEFAULT,
+    ALTER COLUMN role TYPE user_role USING role::user_role,
+    ALTER COLUMN role SET DEFAULT 'user';
+
+DROP TABLE IF EXISTS role_permissions;
+
+DROP TABLE IF EXISTS permissions;
+
+DROP TABLE IF EXISTS roles;
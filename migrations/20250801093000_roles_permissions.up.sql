@@ -0,0 +1,54 @@
+-- Add up migration script here
+
+CREATE TABLE roles (
+    name VARCHAR(50) NOT NULL PRIMARY KEY,
+    description VARCHAR(255),
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE permissions (
+    name VARCHAR(100) NOT NULL PRIMARY KEY,
+    description VARCHAR(255) NOT NULL
+);
+
+CREATE TABLE role_permissions (
+    role VARCHAR(50) NOT NULL REFERENCES roles(name) ON DELETE CASCADE,
+    permission VARCHAR(100) NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission)
+);
+
+INSERT INTO roles (name, description, is_system) VALUES
+    ('admin', 'Full access to every resource', TRUE),
+    ('user', 'Default role of new accounts', TRUE);
+
+INSERT INTO permissions (name, description) VALUES
+    ('posts:create', 'Create posts'),
+    ('posts:update:any', 'Update posts of any user'),
+    ('posts:delete:any', 'Delete posts of any user'),
+    ('users:read', 'List users'),
+    ('users:update:any', 'Update any user'),
+    ('users:delete:any', 'Delete any user'),
+    ('users:role:update', 'Change the role of a user'),
+    ('users:sessions:revoke', 'Sign out every session of a user'),
+    ('mfa:policy:update', 'Require two-factor authentication for a role'),
+    ('roles:manage', 'Create, update and delete roles');
+
+INSERT INTO role_permissions (role, permission)
+SELECT 'admin', name FROM permissions;
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('user', 'posts:create');
+
+ALTER TABLE users
+    ALTER COLUMN role DROP DEFAULT,
+    ALTER COLUMN role TYPE VARCHAR(50) USING role::text,
+    ALTER COLUMN role SET DEFAULT 'user',
+    ADD CONSTRAINT users_role_fkey FOREIGN KEY (role) REFERENCES roles(name);
+
+ALTER TABLE mfa_policies
+    ALTER COLUMN role TYPE VARCHAR(50) USING role::text,
+    ADD CONSTRAINT mfa_policies_role_fkey FOREIGN KEY (role) REFERENCES roles(name) ON DELETE CASCADE;
+
+DROP TYPE IF EXISTS user_role;
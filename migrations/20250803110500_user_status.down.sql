@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+DELETE FROM permissions WHERE name = 'users:suspend';
+
+DROP INDEX IF EXISTS users_deleted_at_idx;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS deleted_at,
+    DROP COLUMN IF EXISTS suspension_reason,
+    DROP COLUMN IF EXISTS suspended_until,
+    DROP COLUMN IF EXISTS status;
+
+DROP TYPE IF EXISTS user_status;
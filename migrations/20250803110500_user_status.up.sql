@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+CREATE TYPE user_status AS ENUM ('active', 'suspended', 'deactivated');
+
+ALTER TABLE users
+    ADD COLUMN status user_status NOT NULL DEFAULT 'active',
+    ADD COLUMN suspended_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN suspension_reason VARCHAR(255),
+    ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX users_deleted_at_idx ON users (deleted_at) WHERE deleted_at IS NOT NULL;
+
+INSERT INTO permissions (name, description) VALUES
+    ('users:suspend', 'Suspend and unsuspend users');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'users:suspend');
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS posts_deleted_at_idx;
+
+ALTER TABLE posts
+    DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+CREATE TABLE post_revisions (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    body TEXT NOT NULL,
+    editor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    reverted_from INTEGER,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (post_id, revision)
+);
+
+INSERT INTO post_revisions (post_id, revision, title, body, editor_id, created_at)
+SELECT id, 1, title, body, user_id, updated_at FROM posts;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS posts_search_vector_idx;
+
+ALTER TABLE posts
+    DROP COLUMN IF EXISTS search_vector;
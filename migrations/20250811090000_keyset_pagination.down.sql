@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS users_created_at_id_idx;
+
+DROP INDEX IF EXISTS posts_user_id_created_at_id_idx;
+
+DROP INDEX IF EXISTS posts_created_at_id_idx;
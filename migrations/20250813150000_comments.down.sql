@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DELETE FROM permissions WHERE name IN ('comments:create', 'comments:update:any', 'comments:delete:any');
+
+DROP TABLE IF EXISTS comments;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+CREATE TABLE comments (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    parent_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS comments_post_id_created_at_idx ON comments (post_id, created_at, id);
+
+CREATE INDEX IF NOT EXISTS comments_parent_id_idx ON comments (parent_id);
+
+INSERT INTO permissions (name, description) VALUES
+    ('comments:create', 'Comment on posts'),
+    ('comments:update:any', 'Edit any comment'),
+    ('comments:delete:any', 'Delete any comment');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'comments:create'),
+    ('admin', 'comments:update:any'),
+    ('admin', 'comments:delete:any'),
+    ('user', 'comments:create');
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS post_tags;
+
+DROP TABLE IF EXISTS tags;
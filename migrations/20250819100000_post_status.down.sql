@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+DELETE FROM permissions WHERE name = 'posts:read:any';
+
+DROP INDEX IF EXISTS posts_scheduled_published_at_idx;
+
+ALTER TABLE posts
+    DROP CONSTRAINT IF EXISTS posts_published_at_check,
+    DROP COLUMN IF EXISTS published_at,
+    DROP COLUMN IF EXISTS status;
+
+DROP TYPE IF EXISTS post_status;
@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TYPE post_status AS ENUM ('draft', 'scheduled', 'published', 'archived');
+
+-- existing posts were live from the moment they were created
+ALTER TABLE posts
+    ADD COLUMN status post_status NOT NULL DEFAULT 'published',
+    ADD COLUMN published_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE posts SET published_at = created_at;
+
+ALTER TABLE posts
+    ALTER COLUMN status SET DEFAULT 'draft',
+    ADD CONSTRAINT posts_published_at_check
+        CHECK (status NOT IN ('scheduled', 'published') OR published_at IS NOT NULL);
+
+CREATE INDEX IF NOT EXISTS posts_scheduled_published_at_idx ON posts (published_at) WHERE status = 'scheduled';
+
+INSERT INTO permissions (name, description) VALUES
+    ('posts:read:any', 'Read drafts, scheduled and archived posts of any user');
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'posts:read:any');
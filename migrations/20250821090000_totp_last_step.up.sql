@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE users
+    ADD COLUMN totp_last_step BIGINT;
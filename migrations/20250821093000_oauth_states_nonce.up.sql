@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+DELETE FROM oauth_states;
+
+ALTER TABLE oauth_states
+    ADD COLUMN nonce VARCHAR(128) NOT NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE oauth_states
+    DROP COLUMN IF EXISTS user_id;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE oauth_states
+    ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS deleted_by;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+ALTER TABLE users
+    ADD COLUMN deleted_by UUID REFERENCES users(id) ON DELETE SET NULL;
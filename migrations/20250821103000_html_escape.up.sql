@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+CREATE FUNCTION html_escape(value TEXT) RETURNS TEXT
+LANGUAGE SQL IMMUTABLE STRICT
+AS $$
+    SELECT replace(replace(replace(replace(replace(value,
+        '&', '&amp;'),
+        '<', '&lt;'),
+        '>', '&gt;'),
+        '"', '&quot;'),
+        '''', '&#39;')
+$$;
@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error as SqlxError, query, query_as};
+use uuid::Uuid;
+
+use crate::{db::DBClient, dtos::api_key_dto::ApiKey};
+
+#[async_trait]
+pub trait ApiKeyExt {
+    async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        name: String,
+        prefix: String,
+        key_hash: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKey, SqlxError>;
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>, SqlxError>;
+
+    async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, SqlxError>;
+
+    async fn use_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, SqlxError>;
+
+    async fn delete_api_key(&self, user_id: Uuid, id: Uuid) -> Result<bool, SqlxError>;
+}
+
+#[async_trait]
+impl ApiKeyExt for DBClient {
+    async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        name: String,
+        prefix: String,
+        key_hash: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ApiKey, SqlxError> {
+        query_as::<_, ApiKey>(
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_api_keys(&self, user_id: Uuid) -> Result<Vec<ApiKey>, SqlxError> {
+        query_as::<_, ApiKey>(
+            r#"
+            SELECT * FROM api_keys
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, SqlxError> {
+        query_as::<_, ApiKey>(
+            r#"
+            SELECT * FROM api_keys
+            WHERE key_hash = $1
+                AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn use_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>, SqlxError> {
+        query_as::<_, ApiKey>(
+            r#"
+            UPDATE api_keys
+            SET last_used_at = NOW()
+            WHERE key_hash = $1
+                AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn delete_api_key(&self, user_id: Uuid, id: Uuid) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            DELETE FROM api_keys
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
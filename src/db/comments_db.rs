@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query, query_as, query_scalar};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::comment_dto::{Comment, CommentOwnership},
+    utils::pagination::offset,
+};
+
+#[async_trait]
+pub trait CommentExt {
+    async fn get_comment(&self, id: Uuid) -> Result<Option<Comment>, SqlxError>;
+
+    async fn get_comment_ownership(&self, id: Uuid) -> Result<Option<CommentOwnership>, SqlxError>;
+
+    async fn get_comments(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, SqlxError>;
+
+    async fn get_comment_threads(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, SqlxError>;
+
+    async fn get_comments_count(&self, post_id: Uuid, roots_only: bool) -> Result<i64, SqlxError>;
+
+    async fn create_comment(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        parent_id: Option<Uuid>,
+        body: String,
+    ) -> Result<Comment, SqlxError>;
+
+    async fn update_comment(
+        &self,
+        post_id: Uuid,
+        id: Uuid,
+        body: String,
+    ) -> Result<Option<Comment>, SqlxError>;
+
+    async fn delete_comment(&self, post_id: Uuid, id: Uuid) -> Result<bool, SqlxError>;
+}
+
+#[async_trait]
+impl CommentExt for DBClient {
+    async fn get_comment(&self, id: Uuid) -> Result<Option<Comment>, SqlxError> {
+        query_as::<_, Comment>(
+            r#"
+            SELECT * FROM comments
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn get_comment_ownership(&self, id: Uuid) -> Result<Option<CommentOwnership>, SqlxError> {
+        query_as::<_, CommentOwnership>(
+            r#"
+            SELECT c.user_id, p.user_id AS post_owner_id
+            FROM comments c
+            JOIN posts p ON p.id = c.post_id
+            WHERE c.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn get_comments(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, SqlxError> {
+        query_as::<_, Comment>(
+            r#"
+            SELECT * FROM comments
+            WHERE post_id = $1
+            ORDER BY created_at, id
+            LIMIT $2
+            OFFSET $3
+            "#,
+        )
+        .bind(post_id)
+        .bind(limit as i64)
+        .bind(offset(page, limit))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // pages over top level comments and returns them together with all of their replies
+    async fn get_comment_threads(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Comment>, SqlxError> {
+        query_as::<_, Comment>(
+            r#"
+            WITH RECURSIVE roots AS (
+                SELECT * FROM comments
+                WHERE post_id = $1 AND parent_id IS NULL
+                ORDER BY created_at, id
+                LIMIT $2
+                OFFSET $3
+            ), threads AS (
+                SELECT * FROM roots
+                UNION ALL
+                SELECT c.* FROM comments c
+                JOIN threads t ON c.parent_id = t.id
+            )
+            SELECT * FROM threads
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(post_id)
+        .bind(limit as i64)
+        .bind(offset(page, limit))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_comments_count(&self, post_id: Uuid, roots_only: bool) -> Result<i64, SqlxError> {
+        query_scalar(
+            r#"
+            SELECT COUNT(*) FROM comments
+            WHERE post_id = $1 AND (NOT $2 OR parent_id IS NULL)
+            "#,
+        )
+        .bind(post_id)
+        .bind(roots_only)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn create_comment(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        parent_id: Option<Uuid>,
+        body: String,
+    ) -> Result<Comment, SqlxError> {
+        query_as::<_, Comment>(
+            r#"
+            INSERT INTO comments (post_id, user_id, parent_id, body)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .bind(parent_id)
+        .bind(body)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn update_comment(
+        &self,
+        post_id: Uuid,
+        id: Uuid,
+        body: String,
+    ) -> Result<Option<Comment>, SqlxError> {
+        query_as::<_, Comment>(
+            r#"
+            UPDATE comments
+            SET body = $3, updated_at = NOW()
+            WHERE post_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(post_id)
+        .bind(id)
+        .bind(body)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn delete_comment(&self, post_id: Uuid, id: Uuid) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            DELETE FROM comments
+            WHERE post_id = $1 AND id = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Encode, Postgres, QueryBuilder, Type};
+
+use crate::utils::pagination::{KeysetValue, ListParams, SortDirection};
+
+// column names are only ever passed as `&'static str` from the db modules, values are always bound
+pub struct ListQuery<'a> {
+    query: QueryBuilder<'a, Postgres>,
+    has_condition: bool,
+}
+
+impl<'a> ListQuery<'a> {
+    pub fn new(select: &str) -> Self {
+        Self {
+            query: QueryBuilder::new(select),
+            has_condition: false,
+        }
+    }
+
+    pub fn condition(&mut self) -> &mut QueryBuilder<'a, Postgres> {
+        self.query.push(match self.has_condition {
+            true => " AND ",
+            false => " WHERE ",
+        });
+        self.has_condition = true;
+
+        &mut self.query
+    }
+
+    pub fn is_null(&mut self, column: &'static str) -> &mut Self {
+        self.condition().push(column).push(" IS NULL");
+        self
+    }
+
+    pub fn eq<T>(&mut self, column: &'static str, value: Option<T>) -> &mut Self
+    where
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.condition().push(column).push(" = ").push_bind(value);
+        }
+        self
+    }
+
+    pub fn from(&mut self, column: &'static str, value: Option<DateTime<Utc>>) -> &mut Self {
+        if let Some(value) = value {
+            self.condition().push(column).push(" >= ").push_bind(value);
+        }
+        self
+    }
+
+    pub fn until(&mut self, column: &'static str, value: Option<DateTime<Utc>>) -> &mut Self {
+        if let Some(value) = value {
+            self.condition().push(column).push(" < ").push_bind(value);
+        }
+        self
+    }
+
+    pub fn starts_with(&mut self, column: &'static str, value: Option<&str>) -> &mut Self {
+        if let Some(value) = value {
+            self.condition()
+                .push(column)
+                .push(" ILIKE ")
+                .push_bind(format!("{}%", escape_like(value)));
+        }
+        self
+    }
+
+    pub fn ends_with(&mut self, column: &'static str, value: Option<&str>) -> &mut Self {
+        if let Some(value) = value {
+            self.condition()
+                .push(column)
+                .push(" ILIKE ")
+                .push_bind(format!("%{}", escape_like(value)));
+        }
+        self
+    }
+
+    // keyset condition for cursors, then the order and the page window
+    pub fn paginate(&mut self, params: &ListParams) -> &mut Self {
+        let column = params.sort.column;
+        let direction = params.fetch_direction();
+
+        if let Some(cursor) = &params.cursor {
+            let comparison = match direction {
+                SortDirection::Asc => ">",
+                SortDirection::Desc => "<",
+            };
+
+            let query = self.condition();
+            query.push(format!("({column}, id) {comparison} ("));
+            match &cursor.value {
+                KeysetValue::Timestamp(timestamp) => query.push_bind(*timestamp),
+                KeysetValue::Text(text) => query.push_bind(text.clone()),
+            };
+            query.push(", ").push_bind(cursor.id).push(")");
+        }
+
+        let direction = direction.as_sql();
+        self.query.push(format!(
+            " ORDER BY {column} {direction}, id {direction} LIMIT "
+        ));
+
+        match params.cursor {
+            Some(_) => {
+                self.query.push_bind(params.limit as i64 + 1);
+            }
+            None => {
+                self.query
+                    .push_bind(params.limit as i64)
+                    .push(" OFFSET ")
+                    .push_bind(params.offset());
+            }
+        }
+        self
+    }
+
+    pub fn builder(&mut self) -> &mut QueryBuilder<'a, Postgres> {
+        &mut self.query
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::utils::pagination::{Cursor, CursorDirection, ListParams};
+
+    const ALLOWED: &[&str] = &["created_at", "title"];
+
+    #[test]
+    fn skips_missing_filters_and_joins_the_rest_with_and() {
+        let mut query = ListQuery::new("SELECT * FROM posts");
+        query
+            .is_null("deleted_at")
+            .eq("user_id", None::<Uuid>)
+            .eq("status", Some("published"))
+            .from("created_at", None)
+            .until("created_at", Some(Utc::now()));
+
+        assert_eq!(
+            query.builder().sql(),
+            "SELECT * FROM posts WHERE deleted_at IS NULL AND status = $1 AND created_at < $2"
+        );
+    }
+
+    #[test]
+    fn escapes_like_wildcards() {
+        assert_eq!(escape_like(r"50%_off\"), r"50\%\_off\\");
+    }
+
+    #[test]
+    fn pages_with_limit_and_offset_without_a_cursor() {
+        let params = ListParams::new(Some(3), Some(20), None, Some("title"), ALLOWED).unwrap();
+
+        let mut query = ListQuery::new("SELECT * FROM posts");
+        query.paginate(&params);
+
+        assert_eq!(
+            query.builder().sql(),
+            "SELECT * FROM posts ORDER BY title ASC, id ASC LIMIT $1 OFFSET $2"
+        );
+        assert_eq!(params.offset(), 40);
+    }
+
+    #[test]
+    fn pages_from_a_prev_cursor_in_reverse() {
+        let cursor = Cursor {
+            sort: "created_at:desc".to_string(),
+            value: KeysetValue::Timestamp(Utc::now()),
+            id: Uuid::new_v4(),
+            direction: CursorDirection::Prev,
+        }
+        .encode();
+        let params = ListParams::new(None, Some(10), Some(&cursor), None, ALLOWED).unwrap();
+
+        let mut query = ListQuery::new("SELECT * FROM posts");
+        query.is_null("deleted_at").paginate(&params);
+
+        assert_eq!(
+            query.builder().sql(),
+            "SELECT * FROM posts WHERE deleted_at IS NULL AND (created_at, id) > ($1, $2) ORDER BY created_at ASC, id ASC LIMIT $3"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{query, query_as};
+
+use crate::{
+    db::DBClient, dtos::auth_dto::LoginAttempt, error::ErrorMessage,
+    utils::login_throttle::LoginAttemptStore,
+};
+
+#[async_trait]
+impl LoginAttemptStore for DBClient {
+    async fn get_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, ErrorMessage> {
+        query_as::<_, LoginAttempt>(
+            r#"
+            SELECT * FROM login_attempts
+            WHERE key = $1
+            "#,
+        )
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ErrorMessage::ServerError)
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        window_sec: i64,
+    ) -> Result<LoginAttempt, ErrorMessage> {
+        query_as::<_, LoginAttempt>(
+            r#"
+            INSERT INTO login_attempts (key, failures, last_failure_at)
+            VALUES ($1, 1, NOW())
+            ON CONFLICT (key) DO UPDATE
+            SET
+                failures = CASE
+                    WHEN login_attempts.last_failure_at < NOW() - MAKE_INTERVAL(secs => $2)
+                    THEN 1
+                    ELSE login_attempts.failures + 1
+                END,
+                last_failure_at = NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(key)
+        .bind(window_sec as f64)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|_| ErrorMessage::ServerError)
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), ErrorMessage> {
+        query(
+            r#"
+            UPDATE login_attempts
+            SET locked_until = $2
+            WHERE key = $1
+            "#,
+        )
+        .bind(key)
+        .bind(until)
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ErrorMessage::ServerError)?;
+
+        Ok(())
+    }
+
+    async fn clear_attempts(&self, key: &str) -> Result<(), ErrorMessage> {
+        query(
+            r#"
+            DELETE FROM login_attempts
+            WHERE key = $1
+            "#,
+        )
+        .bind(key)
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ErrorMessage::ServerError)?;
+
+        Ok(())
+    }
+}
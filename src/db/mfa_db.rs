@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query, query_as, query_scalar};
+use uuid::Uuid;
+
+use crate::{db::DBClient, dtos::user_dto::User};
+
+#[async_trait]
+pub trait MfaExt {
+    async fn set_totp_secret(
+        &self,
+        user_id: Uuid,
+        secret: String,
+    ) -> Result<Option<User>, SqlxError>;
+
+    async fn enable_totp(
+        &self,
+        user_id: Uuid,
+        totp_step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<Option<User>, SqlxError>;
+
+    async fn disable_totp(&self, user_id: Uuid) -> Result<Option<User>, SqlxError>;
+
+    async fn accept_totp_step(&self, user_id: Uuid, totp_step: i64) -> Result<bool, SqlxError>;
+
+    async fn consume_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<bool, SqlxError>;
+
+    async fn is_mfa_required(&self, role: &str) -> Result<bool, SqlxError>;
+
+    async fn set_mfa_required(&self, role: &str, required: bool) -> Result<(), SqlxError>;
+}
+
+#[async_trait]
+impl MfaExt for DBClient {
+    async fn set_totp_secret(
+        &self,
+        user_id: Uuid,
+        secret: String,
+    ) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET totp_secret = $2, totp_last_step = NULL, updated_at = NOW()
+            WHERE id = $1 AND totp_enabled_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(secret)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn enable_totp(
+        &self,
+        user_id: Uuid,
+        totp_step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<Option<User>, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET totp_enabled_at = NOW(), totp_last_step = $2, updated_at = NOW()
+            WHERE id = $1 AND totp_secret IS NOT NULL AND totp_enabled_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(totp_step)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if user.is_none() {
+            return Ok(None);
+        }
+
+        query(
+            r#"
+            DELETE FROM mfa_recovery_codes
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        query(
+            r#"
+            INSERT INTO mfa_recovery_codes (user_id, code_hash)
+            SELECT $1, UNNEST($2::VARCHAR[])
+            "#,
+        )
+        .bind(user_id)
+        .bind(recovery_code_hashes)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn disable_totp(&self, user_id: Uuid) -> Result<Option<User>, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let user = query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        query(
+            r#"
+            DELETE FROM mfa_recovery_codes
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(user)
+    }
+
+    async fn accept_totp_step(&self, user_id: Uuid, totp_step: i64) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE users
+            SET totp_last_step = $2
+            WHERE id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(totp_step)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn consume_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE mfa_recovery_codes
+            SET used_at = NOW()
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_mfa_required(&self, role: &str) -> Result<bool, SqlxError> {
+        Ok(query_scalar::<_, bool>(
+            r#"
+            SELECT required FROM mfa_policies
+            WHERE role = $1
+            "#,
+        )
+        .bind(role)
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(false))
+    }
+
+    async fn set_mfa_required(&self, role: &str, required: bool) -> Result<(), SqlxError> {
+        query(
+            r#"
+            INSERT INTO mfa_policies (role, required)
+            VALUES ($1, $2)
+            ON CONFLICT (role) DO UPDATE
+            SET required = EXCLUDED.required, updated_at = NOW()
+            "#,
+        )
+        .bind(role)
+        .bind(required)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,16 @@
+pub mod api_keys_db;
+pub mod comments_db;
+pub mod list_query;
+pub mod login_attempts_db;
+pub mod mfa_db;
+pub mod oauth_db;
+pub mod post_revisions_db;
 pub mod posts_db;
+pub mod reactions_db;
+pub mod roles_db;
+pub mod sessions_db;
+pub mod tags_db;
+pub mod user_tokens_db;
 pub mod users_db;
 
 use std::error::Error;
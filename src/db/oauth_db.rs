@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error as SqlxError, query, query_as};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::{
+        oauth_dto::{OAuthIdentity, OAuthState},
+        user_dto::User,
+    },
+};
+
+#[async_trait]
+pub trait OAuthExt {
+    async fn create_oauth_state(
+        &self,
+        state_hash: String,
+        provider: &str,
+        code_verifier: String,
+        nonce: String,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<OAuthState, SqlxError>;
+
+    async fn consume_oauth_state(
+        &self,
+        state_hash: &str,
+        provider: &str,
+    ) -> Result<Option<OAuthState>, SqlxError>;
+
+    async fn get_oauth_user(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<User>, SqlxError>;
+
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        subject: &str,
+        email: Option<String>,
+    ) -> Result<OAuthIdentity, SqlxError>;
+}
+
+#[async_trait]
+impl OAuthExt for DBClient {
+    async fn create_oauth_state(
+        &self,
+        state_hash: String,
+        provider: &str,
+        code_verifier: String,
+        nonce: String,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<OAuthState, SqlxError> {
+        query(r#"DELETE FROM oauth_states WHERE expires_at <= NOW()"#)
+            .execute(&self.pool)
+            .await?;
+
+        query_as::<_, OAuthState>(
+            r#"
+            INSERT INTO oauth_states
+                (state_hash, provider, code_verifier, nonce, user_id, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(state_hash)
+        .bind(provider)
+        .bind(code_verifier)
+        .bind(nonce)
+        .bind(user_id)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn consume_oauth_state(
+        &self,
+        state_hash: &str,
+        provider: &str,
+    ) -> Result<Option<OAuthState>, SqlxError> {
+        query_as::<_, OAuthState>(
+            r#"
+            DELETE FROM oauth_states
+            WHERE state_hash = $1
+                AND provider = $2
+                AND expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(state_hash)
+        .bind(provider)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn get_oauth_user(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE oauth_identities
+            SET last_login_at = NOW()
+            FROM users
+            WHERE oauth_identities.user_id = users.id
+                AND oauth_identities.provider = $1
+                AND oauth_identities.subject = $2
+            RETURNING users.*
+            "#,
+        )
+        .bind(provider)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        subject: &str,
+        email: Option<String>,
+    ) -> Result<OAuthIdentity, SqlxError> {
+        query_as::<_, OAuthIdentity>(
+            r#"
+            INSERT INTO oauth_identities (user_id, provider, subject, email)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(subject)
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query_as, query_scalar};
+use uuid::Uuid;
+
+use crate::{db::DBClient, dtos::post_dto::PostRevision, utils::pagination::offset};
+
+#[async_trait]
+pub trait PostRevisionExt {
+    async fn get_post_revisions(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<PostRevision>, SqlxError>;
+
+    async fn get_post_revisions_count(&self, post_id: Uuid) -> Result<i64, SqlxError>;
+
+    async fn get_post_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+    ) -> Result<Option<PostRevision>, SqlxError>;
+}
+
+#[async_trait]
+impl PostRevisionExt for DBClient {
+    async fn get_post_revisions(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<PostRevision>, SqlxError> {
+        query_as::<_, PostRevision>(
+            r#"
+            SELECT * FROM post_revisions
+            WHERE post_id = $1
+            ORDER BY revision DESC
+            LIMIT $2
+            OFFSET $3
+            "#,
+        )
+        .bind(post_id)
+        .bind(limit as i64)
+        .bind(offset(page, limit))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_post_revisions_count(&self, post_id: Uuid) -> Result<i64, SqlxError> {
+        query_scalar(
+            r#"
+            SELECT COUNT(*) FROM post_revisions
+            WHERE post_id = $1
+            "#,
+        )
+        .bind(post_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_post_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+    ) -> Result<Option<PostRevision>, SqlxError> {
+        query_as::<_, PostRevision>(
+            r#"
+            SELECT * FROM post_revisions
+            WHERE post_id = $1 AND revision = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(revision)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
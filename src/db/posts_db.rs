@@ -1,39 +1,67 @@
 use async_trait::async_trait;
-use sqlx::{Error as SqlxError, query, query_as, query_scalar};
+use chrono::{DateTime, Utc};
+use sqlx::{Error as SqlxError, PgConnection, query, query_as, query_scalar};
 use uuid::Uuid;
 
-use crate::{db::DBClient, dtos::post_dto::Post};
+use crate::{
+    db::{DBClient, list_query::ListQuery},
+    dtos::{
+        post_dto::{CreatePostDto, Post, PostFilter, PostSearchResult, UpdatePostDto},
+        tag_dto::TagMatch,
+    },
+    utils::pagination::{ListParams, offset},
+};
 
 #[async_trait]
 pub trait PostExt {
     async fn get_post_by_id(&self, id: Uuid) -> Result<Option<Post>, SqlxError>;
 
-    async fn get_posts(&self, page: usize, limit: usize) -> Result<Vec<Post>, SqlxError>;
+    async fn get_posts(
+        &self,
+        filter: &PostFilter,
+        params: &ListParams,
+    ) -> Result<Vec<Post>, SqlxError>;
+
+    async fn get_posts_count(&self, filter: &PostFilter) -> Result<i64, SqlxError>;
 
-    async fn get_posts_by_user_id(
+    async fn search_posts(
         &self,
-        user_id: Uuid,
+        search: &str,
         page: usize,
         limit: usize,
-    ) -> Result<Vec<Post>, SqlxError>;
+    ) -> Result<Vec<PostSearchResult>, SqlxError>;
 
-    async fn get_posts_count(&self) -> Result<i64, SqlxError>;
+    async fn search_posts_count(&self, search: &str) -> Result<i64, SqlxError>;
 
-    async fn create_post(
-        &self,
-        user_id: Uuid,
-        title: String,
-        body: String,
-    ) -> Result<Post, SqlxError>;
+    async fn create_post(&self, user_id: Uuid, data: CreatePostDto) -> Result<Post, SqlxError>;
 
     async fn update_post(
         &self,
         id: Uuid,
-        title: Option<String>,
-        body: Option<String>,
+        editor_id: Uuid,
+        data: UpdatePostDto,
+        reverted_from: Option<i32>,
+        expected_updated_at: Option<DateTime<Utc>>,
     ) -> Result<Option<Post>, SqlxError>;
 
     async fn delete_post(&self, id: Uuid) -> Result<bool, SqlxError>;
+
+    async fn get_deleted_post_by_id(&self, id: Uuid) -> Result<Option<Post>, SqlxError>;
+
+    async fn get_deleted_posts(
+        &self,
+        user_id: Option<Uuid>,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Post>, SqlxError>;
+
+    async fn get_deleted_posts_count(&self, user_id: Option<Uuid>) -> Result<i64, SqlxError>;
+
+    async fn restore_post(&self, id: Uuid) -> Result<Option<Post>, SqlxError>;
+
+    async fn purge_deleted_posts(&self, retention_days: i64) -> Result<u64, SqlxError>;
+
+    async fn publish_scheduled_posts(&self) -> Result<u64, SqlxError>;
 }
 
 #[async_trait]
@@ -42,7 +70,7 @@ impl PostExt for DBClient {
         query_as::<_, Post>(
             r#"
             SELECT * FROM posts
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
            "#,
         )
         .bind(id)
@@ -50,108 +78,374 @@ impl PostExt for DBClient {
         .await
     }
 
-    async fn get_posts(&self, page: usize, limit: usize) -> Result<Vec<Post>, SqlxError> {
-        query_as::<_, Post>(
-            r#"
-            SELECT * FROM posts
-            ORDER BY created_at DESC
-            LIMIT $1 
-            OFFSET $2
-            "#,
-        )
-        .bind(limit as i64)
-        .bind(((page - 1) * limit) as i64)
-        .fetch_all(&self.pool)
-        .await
+    async fn get_posts(
+        &self,
+        filter: &PostFilter,
+        params: &ListParams,
+    ) -> Result<Vec<Post>, SqlxError> {
+        let mut query = ListQuery::new("SELECT * FROM posts");
+        post_filters(&mut query, filter).paginate(params);
+
+        query
+            .builder()
+            .build_query_as::<Post>()
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn get_posts_count(&self, filter: &PostFilter) -> Result<i64, SqlxError> {
+        let mut query = ListQuery::new("SELECT COUNT(*) FROM posts");
+        post_filters(&mut query, filter);
+
+        query
+            .builder()
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
+            .await
     }
 
-    async fn get_posts_by_user_id(
+    async fn search_posts(
         &self,
-        user_id: Uuid,
+        search: &str,
         page: usize,
         limit: usize,
-    ) -> Result<Vec<Post>, SqlxError> {
-        query_as::<_, Post>(
+    ) -> Result<Vec<PostSearchResult>, SqlxError> {
+        // headlines are expensive, so they are only built for the rows of the requested page,
+        // the text is escaped first so the <b> tags are the only markup in a headline
+        query_as::<_, PostSearchResult>(
             r#"
-            SELECT * FROM posts 
-            WHERE user_id = $1 
-            ORDER BY created_at DESC 
-            LIMIT $2 
-            OFFSET $3
+            SELECT
+                p.id, p.user_id, p.title, p.body, p.rank, p.created_at, p.updated_at,
+                ts_headline('english', html_escape(p.title), p.query, 'HighlightAll=true') AS title_highlight,
+                ts_headline('english', html_escape(p.body), p.query, 'MaxFragments=2, MaxWords=30, MinWords=10') AS body_highlight
+            FROM (
+                SELECT posts.*, query, ts_rank(search_vector, query) AS rank
+                FROM posts, websearch_to_tsquery('english', $1) query
+                WHERE deleted_at IS NULL AND status = 'published' AND search_vector @@ query
+                ORDER BY rank DESC, created_at DESC
+                LIMIT $2
+                OFFSET $3
+            ) p
+            ORDER BY p.rank DESC, p.created_at DESC
             "#,
         )
-        .bind(user_id)
+        .bind(search)
         .bind(limit as i64)
-        .bind(((page - 1) * limit) as i64)
+        .bind(offset(page, limit))
         .fetch_all(&self.pool)
         .await
     }
 
-    async fn get_posts_count(&self) -> Result<i64, SqlxError> {
+    async fn search_posts_count(&self, search: &str) -> Result<i64, SqlxError> {
         query_scalar(
             r#"
             SELECT COUNT(*) FROM posts
+            WHERE deleted_at IS NULL
+                AND status = 'published'
+                AND search_vector @@ websearch_to_tsquery('english', $1)
             "#,
         )
+        .bind(search)
         .fetch_one(&self.pool)
         .await
     }
 
-    async fn create_post(
-        &self,
-        user_id: Uuid,
-        title: String,
-        body: String,
-    ) -> Result<Post, SqlxError> {
-        query_as::<_, Post>(
+    async fn create_post(&self, user_id: Uuid, data: CreatePostDto) -> Result<Post, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let post = query_as::<_, Post>(
             r#"
-            INSERT INTO posts (user_id, title, body)
-            VALUES ($1, $2, $3)
+            INSERT INTO posts (user_id, title, body, status, published_at)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING *
             "#,
         )
         .bind(user_id)
-        .bind(title)
-        .bind(body)
-        .fetch_one(&self.pool)
-        .await
+        .bind(data.title)
+        .bind(data.body)
+        .bind(data.status.unwrap_or_default())
+        .bind(data.published_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        query(
+            r#"
+            INSERT INTO post_revisions (post_id, revision, title, body, editor_id)
+            VALUES ($1, 1, $2, $3, $4)
+            "#,
+        )
+        .bind(post.id)
+        .bind(&post.title)
+        .bind(&post.body)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        set_post_tags(&mut tx, post.id, &data.tags.unwrap_or_default()).await?;
+
+        tx.commit().await?;
+
+        Ok(post)
     }
 
     async fn update_post(
         &self,
         id: Uuid,
-        title: Option<String>,
-        body: Option<String>,
+        editor_id: Uuid,
+        data: UpdatePostDto,
+        reverted_from: Option<i32>,
+        expected_updated_at: Option<DateTime<Utc>>,
     ) -> Result<Option<Post>, SqlxError> {
-        query_as::<_, Post>(
+        let mut tx = self.pool.begin().await?;
+
+        // the row lock serializes the revision numbers of a post
+        let current = query_as::<_, (String, String)>(
+            r#"
+            SELECT title, body FROM posts
+            WHERE id = $1 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((current_title, current_body)) = current else {
+            return Ok(None);
+        };
+
+        let post = query_as::<_, Post>(
             r#"
             UPDATE posts
             SET 
                 title = COALESCE($2, title), 
                 body = COALESCE($3, body),
+                status = COALESCE($4, status),
+                published_at = COALESCE($5, published_at),
                 updated_at = NOW()
             WHERE id = $1
+                AND deleted_at IS NULL
+                AND ($6::TIMESTAMPTZ IS NULL OR updated_at = $6)
             RETURNING *
             "#,
         )
         .bind(id)
-        .bind(title)
-        .bind(body)
+        .bind(data.title)
+        .bind(data.body)
+        .bind(data.status)
+        .bind(data.published_at)
+        .bind(expected_updated_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(post) = post else {
+            return Ok(None);
+        };
+
+        // revisions track the content, status and tag changes do not add one
+        if post.title != current_title || post.body != current_body {
+            query(
+                r#"
+                INSERT INTO post_revisions (post_id, revision, title, body, editor_id, reverted_from)
+                SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4, $5
+                FROM post_revisions
+                WHERE post_id = $1
+                "#,
+            )
+            .bind(post.id)
+            .bind(&post.title)
+            .bind(&post.body)
+            .bind(editor_id)
+            .bind(reverted_from)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(tags) = data.tags {
+            set_post_tags(&mut tx, post.id, &tags).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(Some(post))
+    }
+
+    async fn delete_post(&self, id: Uuid) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE posts
+            SET deleted_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_deleted_post_by_id(&self, id: Uuid) -> Result<Option<Post>, SqlxError> {
+        query_as::<_, Post>(
+            r#"
+            SELECT * FROM posts
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(id)
         .fetch_optional(&self.pool)
         .await
     }
 
-    async fn delete_post(&self, id: Uuid) -> Result<bool, SqlxError> {
-        let result = query!(
+    async fn get_deleted_posts(
+        &self,
+        user_id: Option<Uuid>,
+        page: usize,
+        limit: usize,
+    ) -> Result<Vec<Post>, SqlxError> {
+        query_as::<_, Post>(
+            r#"
+            SELECT * FROM posts
+            WHERE deleted_at IS NOT NULL AND ($1::UUID IS NULL OR user_id = $1)
+            ORDER BY deleted_at DESC
+            LIMIT $2
+            OFFSET $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(limit as i64)
+        .bind(offset(page, limit))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_deleted_posts_count(&self, user_id: Option<Uuid>) -> Result<i64, SqlxError> {
+        query_scalar(
+            r#"
+            SELECT COUNT(*) FROM posts
+            WHERE deleted_at IS NOT NULL AND ($1::UUID IS NULL OR user_id = $1)
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn restore_post(&self, id: Uuid) -> Result<Option<Post>, SqlxError> {
+        query_as::<_, Post>(
+            r#"
+            UPDATE posts
+            SET deleted_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn purge_deleted_posts(&self, retention_days: i64) -> Result<u64, SqlxError> {
+        let result = query(
             r#"
             DELETE FROM posts
-            WHERE id = $1
+            WHERE deleted_at <= NOW() - make_interval(days => $1::INT)
             "#,
-            id,
         )
+        .bind(retention_days)
         .execute(&self.pool)
         .await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(result.rows_affected())
+    }
+
+    async fn publish_scheduled_posts(&self) -> Result<u64, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE posts
+            SET status = 'published', updated_at = NOW()
+            WHERE status = 'scheduled' AND published_at <= NOW() AND deleted_at IS NULL
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+fn post_filters<'a, 'q>(
+    query: &'q mut ListQuery<'a>,
+    filter: &'a PostFilter,
+) -> &'q mut ListQuery<'a> {
+    query
+        .is_null("deleted_at")
+        .eq("status", Some(filter.status))
+        .eq("user_id", filter.author)
+        .from("created_at", filter.created_after)
+        .until("created_at", filter.created_before)
+        .starts_with("title", filter.title_prefix.as_deref());
+
+    if !filter.tags.is_empty() {
+        let condition = query.condition();
+        condition
+            .push(
+                "id IN (SELECT pt.post_id FROM post_tags pt JOIN tags t ON t.id = pt.tag_id WHERE t.slug = ANY(",
+            )
+            .push_bind(filter.tags.as_slice())
+            .push(")");
+
+        // the slugs are deduplicated, so matching all of them means one row per requested tag
+        if filter.tag_match == TagMatch::All {
+            condition
+                .push(" GROUP BY pt.post_id HAVING COUNT(*) = ")
+                .push_bind(filter.tags.len() as i64);
+        }
+
+        condition.push(")");
     }
+
+    query
+}
+
+// replaces the tags of a post, unknown slugs are created on the way
+async fn set_post_tags(
+    conn: &mut PgConnection,
+    post_id: Uuid,
+    tags: &[String],
+) -> Result<(), SqlxError> {
+    query(
+        r#"
+        INSERT INTO tags (slug)
+        SELECT UNNEST($1::VARCHAR[])
+        ON CONFLICT (slug) DO NOTHING
+        "#,
+    )
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    query(
+        r#"
+        DELETE FROM post_tags
+        WHERE post_id = $1
+        "#,
+    )
+    .bind(post_id)
+    .execute(&mut *conn)
+    .await?;
+
+    query(
+        r#"
+        INSERT INTO post_tags (post_id, tag_id)
+        SELECT $1, id FROM tags
+        WHERE slug = ANY($2)
+        "#,
+    )
+    .bind(post_id)
+    .bind(tags)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
 }
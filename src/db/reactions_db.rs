@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query, query_as};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::post_dto::{ReactionCount, UserReaction},
+};
+
+#[async_trait]
+pub trait ReactionExt {
+    async fn add_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<bool, SqlxError>;
+
+    async fn remove_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<bool, SqlxError>;
+
+    async fn get_reaction_counts(&self, post_ids: &[Uuid])
+    -> Result<Vec<ReactionCount>, SqlxError>;
+
+    async fn get_user_reactions(
+        &self,
+        user_id: Uuid,
+        post_ids: &[Uuid],
+    ) -> Result<Vec<UserReaction>, SqlxError>;
+}
+
+#[async_trait]
+impl ReactionExt for DBClient {
+    // the per-kind count is only touched when the reaction row actually changes, so repeats are no-ops
+    async fn add_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<bool, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = query(
+            r#"
+            INSERT INTO post_reactions (post_id, user_id, kind)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .bind(kind)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        query(
+            r#"
+            INSERT INTO post_reaction_counts (post_id, kind, count)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (post_id, kind) DO UPDATE
+            SET count = post_reaction_counts.count + 1
+            "#,
+        )
+        .bind(post_id)
+        .bind(kind)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn remove_reaction(
+        &self,
+        post_id: Uuid,
+        user_id: Uuid,
+        kind: &str,
+    ) -> Result<bool, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = query(
+            r#"
+            DELETE FROM post_reactions
+            WHERE post_id = $1 AND user_id = $2 AND kind = $3
+            "#,
+        )
+        .bind(post_id)
+        .bind(user_id)
+        .bind(kind)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        query(
+            r#"
+            UPDATE post_reaction_counts
+            SET count = count - 1
+            WHERE post_id = $1 AND kind = $2
+            "#,
+        )
+        .bind(post_id)
+        .bind(kind)
+        .execute(&mut *tx)
+        .await?;
+
+        query(
+            r#"
+            DELETE FROM post_reaction_counts
+            WHERE post_id = $1 AND kind = $2 AND count <= 0
+            "#,
+        )
+        .bind(post_id)
+        .bind(kind)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn get_reaction_counts(
+        &self,
+        post_ids: &[Uuid],
+    ) -> Result<Vec<ReactionCount>, SqlxError> {
+        query_as::<_, ReactionCount>(
+            r#"
+            SELECT post_id, kind, count FROM post_reaction_counts
+            WHERE post_id = ANY($1)
+            "#,
+        )
+        .bind(post_ids)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_user_reactions(
+        &self,
+        user_id: Uuid,
+        post_ids: &[Uuid],
+    ) -> Result<Vec<UserReaction>, SqlxError> {
+        query_as::<_, UserReaction>(
+            r#"
+            SELECT post_id, kind FROM post_reactions
+            WHERE user_id = $1 AND post_id = ANY($2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(post_ids)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query, query_as, query_scalar};
+
+use crate::{
+    db::DBClient,
+    dtos::role_dto::{PermissionInfo, Role},
+};
+
+const SELECT_ROLES: &str = r#"
+    SELECT r.name, r.description, r.is_system, r.created_at, r.updated_at,
+        COALESCE(
+            ARRAY_AGG(rp.permission ORDER BY rp.permission) FILTER (WHERE rp.permission IS NOT NULL),
+            '{}'
+        ) AS permissions
+    FROM roles r
+    LEFT JOIN role_permissions rp ON rp.role = r.name
+"#;
+
+#[async_trait]
+pub trait RoleExt {
+    async fn get_roles(&self) -> Result<Vec<Role>, SqlxError>;
+
+    async fn get_role(&self, name: &str) -> Result<Option<Role>, SqlxError>;
+
+    async fn get_permissions(&self) -> Result<Vec<PermissionInfo>, SqlxError>;
+
+    async fn create_role(
+        &self,
+        name: String,
+        description: Option<String>,
+        permissions: Vec<String>,
+    ) -> Result<Option<Role>, SqlxError>;
+
+    async fn update_role(
+        &self,
+        name: &str,
+        description: Option<String>,
+        permissions: Option<Vec<String>>,
+    ) -> Result<Option<Role>, SqlxError>;
+
+    async fn delete_role(&self, name: &str) -> Result<bool, SqlxError>;
+
+    async fn is_role_assigned(&self, name: &str) -> Result<bool, SqlxError>;
+
+    async fn role_has_permission(&self, role: &str, permission: &str) -> Result<bool, SqlxError>;
+}
+
+#[async_trait]
+impl RoleExt for DBClient {
+    async fn get_roles(&self) -> Result<Vec<Role>, SqlxError> {
+        query_as::<_, Role>(&format!("{SELECT_ROLES} GROUP BY r.name ORDER BY r.name"))
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn get_role(&self, name: &str) -> Result<Option<Role>, SqlxError> {
+        query_as::<_, Role>(&format!("{SELECT_ROLES} WHERE r.name = $1 GROUP BY r.name"))
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn get_permissions(&self) -> Result<Vec<PermissionInfo>, SqlxError> {
+        query_as::<_, PermissionInfo>(
+            r#"
+            SELECT * FROM permissions
+            ORDER BY name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn create_role(
+        &self,
+        name: String,
+        description: Option<String>,
+        permissions: Vec<String>,
+    ) -> Result<Option<Role>, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let created = query(
+            r#"
+            INSERT INTO roles (name, description)
+            VALUES ($1, $2)
+            ON CONFLICT (name) DO NOTHING
+            "#,
+        )
+        .bind(&name)
+        .bind(description)
+        .execute(&mut *tx)
+        .await?;
+
+        if created.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        query(
+            r#"
+            INSERT INTO role_permissions (role, permission)
+            SELECT $1, UNNEST($2::VARCHAR[])
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(&name)
+        .bind(permissions)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_role(&name).await
+    }
+
+    async fn update_role(
+        &self,
+        name: &str,
+        description: Option<String>,
+        permissions: Option<Vec<String>>,
+    ) -> Result<Option<Role>, SqlxError> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated = query(
+            r#"
+            UPDATE roles
+            SET description = COALESCE($2, description), updated_at = NOW()
+            WHERE name = $1
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        if let Some(permissions) = permissions {
+            query(
+                r#"
+                DELETE FROM role_permissions
+                WHERE role = $1
+                "#,
+            )
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+
+            query(
+                r#"
+                INSERT INTO role_permissions (role, permission)
+                SELECT $1, UNNEST($2::VARCHAR[])
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(name)
+            .bind(permissions)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.get_role(name).await
+    }
+
+    async fn delete_role(&self, name: &str) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            DELETE FROM roles
+            WHERE name = $1 AND is_system = FALSE
+            "#,
+        )
+        .bind(name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn is_role_assigned(&self, name: &str) -> Result<bool, SqlxError> {
+        query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM users WHERE role = $1)
+            "#,
+        )
+        .bind(name)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn role_has_permission(&self, role: &str, permission: &str) -> Result<bool, SqlxError> {
+        query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM role_permissions
+                WHERE role = $1 AND permission = $2
+            )
+            "#,
+        )
+        .bind(role)
+        .bind(permission)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
@@ -0,0 +1,160 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error as SqlxError, query, query_as};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::session_dto::{RefreshToken, Session},
+    utils::client_info::ClientInfo,
+};
+
+#[async_trait]
+pub trait SessionExt {
+    async fn create_refresh_token(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+        client: ClientInfo,
+    ) -> Result<RefreshToken, SqlxError>;
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, SqlxError>;
+
+    async fn rotate_refresh_token(&self, id: Uuid) -> Result<Option<RefreshToken>, SqlxError>;
+
+    async fn revoke_token_family(&self, family_id: Uuid) -> Result<u64, SqlxError>;
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, SqlxError>;
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, SqlxError>;
+
+    async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<u64, SqlxError>;
+}
+
+#[async_trait]
+impl SessionExt for DBClient {
+    async fn create_refresh_token(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+        client: ClientInfo,
+    ) -> Result<RefreshToken, SqlxError> {
+        query_as::<_, RefreshToken>(
+            r#"
+            INSERT INTO refresh_tokens
+                (user_id, family_id, token_hash, expires_at, user_agent, ip_address)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(family_id)
+        .bind(token_hash)
+        .bind(expires_at)
+        .bind(client.user_agent)
+        .bind(client.ip_address)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>, SqlxError> {
+        query_as::<_, RefreshToken>(
+            r#"
+            SELECT * FROM refresh_tokens
+            WHERE token_hash = $1
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn rotate_refresh_token(&self, id: Uuid) -> Result<Option<RefreshToken>, SqlxError> {
+        query_as::<_, RefreshToken>(
+            r#"
+            UPDATE refresh_tokens
+            SET rotated_at = NOW()
+            WHERE id = $1
+                AND rotated_at IS NULL
+                AND revoked_at IS NULL
+                AND expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn revoke_token_family(&self, family_id: Uuid) -> Result<u64, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE family_id = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(family_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_sessions(&self, user_id: Uuid) -> Result<Vec<Session>, SqlxError> {
+        query_as::<_, Session>(
+            r#"
+            SELECT
+                family_id AS id,
+                (ARRAY_AGG(user_agent ORDER BY created_at DESC))[1] AS user_agent,
+                (ARRAY_AGG(ip_address ORDER BY created_at DESC))[1] AS ip_address,
+                MIN(created_at) AS created_at,
+                MAX(created_at) AS last_used_at,
+                MAX(expires_at) AS expires_at
+            FROM refresh_tokens
+            WHERE user_id = $1
+            GROUP BY family_id
+            HAVING BOOL_AND(revoked_at IS NULL) AND MAX(expires_at) > NOW()
+            ORDER BY last_used_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND family_id = $2 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_user_sessions(&self, user_id: Uuid) -> Result<u64, SqlxError> {
+        let result = query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
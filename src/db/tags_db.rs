@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use sqlx::{Error as SqlxError, query_as, query_scalar};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::tag_dto::{PostTag, Tag},
+    utils::pagination::offset,
+};
+
+#[async_trait]
+pub trait TagExt {
+    async fn get_tags(&self, page: usize, limit: usize) -> Result<Vec<Tag>, SqlxError>;
+
+    async fn get_tags_count(&self) -> Result<i64, SqlxError>;
+
+    async fn get_post_tags(&self, post_ids: &[Uuid]) -> Result<Vec<PostTag>, SqlxError>;
+}
+
+#[async_trait]
+impl TagExt for DBClient {
+    // only published posts that are not in the trash are counted
+    async fn get_tags(&self, page: usize, limit: usize) -> Result<Vec<Tag>, SqlxError> {
+        query_as::<_, Tag>(
+            r#"
+            SELECT t.slug, COUNT(*) AS post_count
+            FROM tags t
+            JOIN post_tags pt ON pt.tag_id = t.id
+            JOIN posts p ON p.id = pt.post_id
+            WHERE p.deleted_at IS NULL AND p.status = 'published'
+            GROUP BY t.slug
+            ORDER BY post_count DESC, t.slug
+            LIMIT $1
+            OFFSET $2
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(offset(page, limit))
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn get_tags_count(&self) -> Result<i64, SqlxError> {
+        query_scalar(
+            r#"
+            SELECT COUNT(DISTINCT pt.tag_id)
+            FROM post_tags pt
+            JOIN posts p ON p.id = pt.post_id
+            WHERE p.deleted_at IS NULL AND p.status = 'published'
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_post_tags(&self, post_ids: &[Uuid]) -> Result<Vec<PostTag>, SqlxError> {
+        query_as::<_, PostTag>(
+            r#"
+            SELECT pt.post_id, t.slug
+            FROM post_tags pt
+            JOIN tags t ON t.id = pt.tag_id
+            WHERE pt.post_id = ANY($1)
+            ORDER BY t.slug
+            "#,
+        )
+        .bind(post_ids)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
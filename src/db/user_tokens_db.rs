@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Error as SqlxError, query, query_as};
+use uuid::Uuid;
+
+use crate::{
+    db::DBClient,
+    dtos::user_token_dto::{UserToken, UserTokenKind},
+};
+
+#[async_trait]
+pub trait UserTokenExt {
+    async fn create_user_token(
+        &self,
+        user_id: Uuid,
+        kind: UserTokenKind,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<UserToken, SqlxError>;
+
+    async fn get_user_token(
+        &self,
+        kind: UserTokenKind,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>, SqlxError>;
+
+    async fn consume_user_token(
+        &self,
+        kind: UserTokenKind,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>, SqlxError>;
+
+    async fn delete_user_tokens(
+        &self,
+        user_id: Uuid,
+        kind: UserTokenKind,
+    ) -> Result<u64, SqlxError>;
+}
+
+#[async_trait]
+impl UserTokenExt for DBClient {
+    async fn create_user_token(
+        &self,
+        user_id: Uuid,
+        kind: UserTokenKind,
+        token_hash: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<UserToken, SqlxError> {
+        query_as::<_, UserToken>(
+            r#"
+            INSERT INTO user_tokens (user_id, kind, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(kind)
+        .bind(token_hash)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    async fn get_user_token(
+        &self,
+        kind: UserTokenKind,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>, SqlxError> {
+        query_as::<_, UserToken>(
+            r#"
+            SELECT * FROM user_tokens
+            WHERE kind = $1
+                AND token_hash = $2
+                AND used_at IS NULL
+                AND expires_at > NOW()
+            "#,
+        )
+        .bind(kind)
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn consume_user_token(
+        &self,
+        kind: UserTokenKind,
+        token_hash: &str,
+    ) -> Result<Option<UserToken>, SqlxError> {
+        query_as::<_, UserToken>(
+            r#"
+            UPDATE user_tokens
+            SET used_at = NOW()
+            WHERE kind = $1
+                AND token_hash = $2
+                AND used_at IS NULL
+                AND expires_at > NOW()
+            RETURNING *
+            "#,
+        )
+        .bind(kind)
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn delete_user_tokens(
+        &self,
+        user_id: Uuid,
+        kind: UserTokenKind,
+    ) -> Result<u64, SqlxError> {
+        let result = query(
+            r#"
+            DELETE FROM user_tokens
+            WHERE user_id = $1 AND kind = $2 AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(kind)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,10 +1,12 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{Error as SqlxError, query, query_as};
 use uuid::Uuid;
 
 use crate::{
-    db::DBClient,
-    dtos::user_dto::{User, UserRole},
+    db::{DBClient, list_query::ListQuery},
+    dtos::user_dto::{User, UserFilter},
+    utils::pagination::ListParams,
 };
 
 #[async_trait]
@@ -16,9 +18,13 @@ pub trait UserExt {
         email: Option<&str>,
     ) -> Result<Option<User>, SqlxError>;
 
-    async fn get_users(&self, page: usize, limit: usize) -> Result<Vec<User>, SqlxError>;
+    async fn get_users(
+        &self,
+        filter: &UserFilter,
+        params: &ListParams,
+    ) -> Result<Vec<User>, SqlxError>;
 
-    async fn get_user_count(&self) -> Result<i64, SqlxError>;
+    async fn get_user_count(&self, filter: &UserFilter) -> Result<i64, SqlxError>;
 
     async fn create_user(
         &self,
@@ -32,11 +38,27 @@ pub trait UserExt {
         id: Uuid,
         email: Option<String>,
         password: Option<String>,
+        expected_updated_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<User>, SqlxError>;
+
+    async fn update_user_role(&self, id: Uuid, role: &str) -> Result<User, SqlxError>;
+
+    async fn verify_user_email(&self, id: Uuid) -> Result<Option<User>, SqlxError>;
+
+    async fn suspend_user(
+        &self,
+        id: Uuid,
+        reason: String,
+        until: Option<DateTime<Utc>>,
     ) -> Result<Option<User>, SqlxError>;
 
-    async fn update_user_role(&self, id: Uuid, role: UserRole) -> Result<User, SqlxError>;
+    async fn unsuspend_user(&self, id: Uuid) -> Result<Option<User>, SqlxError>;
 
-    async fn delete_user(&self, id: Uuid) -> Result<bool, SqlxError>;
+    async fn deactivate_user(&self, id: Uuid, actor_id: Uuid) -> Result<Option<User>, SqlxError>;
+
+    async fn reactivate_user(&self, id: Uuid, grace_days: i64) -> Result<Option<User>, SqlxError>;
+
+    async fn purge_deactivated_users(&self, grace_days: i64) -> Result<u64, SqlxError>;
 }
 
 #[async_trait]
@@ -70,29 +92,30 @@ impl UserExt for DBClient {
         }
     }
 
-    async fn get_users(&self, page: usize, limit: usize) -> Result<Vec<User>, SqlxError> {
-        query_as::<_, User>(
-            r#"
-            SELECT * FROM users
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit as i64)
-        .bind(((page - 1) * limit) as i64)
-        .fetch_all(&self.pool)
-        .await
+    async fn get_users(
+        &self,
+        filter: &UserFilter,
+        params: &ListParams,
+    ) -> Result<Vec<User>, SqlxError> {
+        let mut query = ListQuery::new("SELECT * FROM users");
+        user_filters(&mut query, filter).paginate(params);
+
+        query
+            .builder()
+            .build_query_as::<User>()
+            .fetch_all(&self.pool)
+            .await
     }
 
-    async fn get_user_count(&self) -> Result<i64, SqlxError> {
-        Ok(query_as::<_, (i64,)>(
-            r#"
-            SELECT COUNT(*) FROM users
-            "#,
-        )
-        .fetch_one(&self.pool)
-        .await?
-        .0)
+    async fn get_user_count(&self, filter: &UserFilter) -> Result<i64, SqlxError> {
+        let mut query = ListQuery::new("SELECT COUNT(*) FROM users");
+        user_filters(&mut query, filter);
+
+        query
+            .builder()
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
+            .await
     }
 
     async fn create_user(
@@ -120,6 +143,7 @@ impl UserExt for DBClient {
         id: Uuid,
         email: Option<String>,
         password: Option<String>,
+        expected_updated_at: Option<DateTime<Utc>>,
     ) -> Result<Option<User>, SqlxError> {
         Ok(query_as::<_, User>(
             r#"
@@ -127,23 +151,31 @@ impl UserExt for DBClient {
             SET
                 email = COALESCE($1, email),
                 password = COALESCE($2, password),
+                token_version = CASE
+                    WHEN $2 IS NULL THEN token_version
+                    ELSE token_version + 1
+                END,
                 updated_at = NOW()
-            WHERE id = $3
+            WHERE id = $3 AND ($4::TIMESTAMPTZ IS NULL OR updated_at = $4)
             RETURNING *
             "#,
         )
         .bind(email)
         .bind(password)
         .bind(id)
+        .bind(expected_updated_at)
         .fetch_optional(&self.pool)
         .await?)
     }
 
-    async fn update_user_role(&self, id: Uuid, role: UserRole) -> Result<User, SqlxError> {
+    async fn update_user_role(&self, id: Uuid, role: &str) -> Result<User, SqlxError> {
         query_as::<_, User>(
             r#"
             UPDATE users
-            SET role = $1
+            SET
+                role = $1,
+                token_version = token_version + 1,
+                updated_at = NOW()
             WHERE id = $2
             RETURNING *
             "#,
@@ -154,17 +186,146 @@ impl UserExt for DBClient {
         .await
     }
 
-    async fn delete_user(&self, id: Uuid) -> Result<bool, SqlxError> {
-        let result = query!(
+    async fn verify_user_email(&self, id: Uuid) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
             r#"
-            DELETE FROM users
+            UPDATE users
+            SET
+                email_verified_at = COALESCE(email_verified_at, NOW()),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn suspend_user(
+        &self,
+        id: Uuid,
+        reason: String,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET
+                status = 'suspended',
+                suspension_reason = $2,
+                suspended_until = $3,
+                token_version = token_version + 1,
+                updated_at = NOW()
+            WHERE id = $1 AND status <> 'deactivated'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(reason)
+        .bind(until)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn unsuspend_user(&self, id: Uuid) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET
+                status = 'active',
+                suspension_reason = NULL,
+                suspended_until = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'suspended'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn deactivate_user(&self, id: Uuid, actor_id: Uuid) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET
+                status = 'deactivated',
+                deleted_at = NOW(),
+                deleted_by = $2,
+                token_version = token_version + 1,
+                updated_at = NOW()
+            WHERE id = $1 AND status <> 'deactivated'
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(actor_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    // only self deleted accounts can be restored, a suspension set before the
+    // deletion still applies after the restore
+    async fn reactivate_user(&self, id: Uuid, grace_days: i64) -> Result<Option<User>, SqlxError> {
+        query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET
+                status = CASE
+                    WHEN suspension_reason IS NULL THEN 'active'::user_status
+                    ELSE 'suspended'::user_status
+                END,
+                deleted_at = NULL,
+                deleted_by = NULL,
+                updated_at = NOW()
             WHERE id = $1
+                AND status = 'deactivated'
+                AND deleted_by = id
+                AND deleted_at > NOW() - make_interval(days => $2::INT)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(grace_days)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn purge_deactivated_users(&self, grace_days: i64) -> Result<u64, SqlxError> {
+        let result = query(
+            r#"
+            DELETE FROM users
+            WHERE status = 'deactivated'
+                AND deleted_at <= NOW() - make_interval(days => $1::INT)
             "#,
-            id,
         )
+        .bind(grace_days)
         .execute(&self.pool)
         .await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(result.rows_affected())
     }
 }
+
+fn user_filters<'a, 'q>(
+    query: &'q mut ListQuery<'a>,
+    filter: &'a UserFilter,
+) -> &'q mut ListQuery<'a> {
+    // deactivated accounts are gone for everyone but the purge job
+    query.condition().push("status <> 'deactivated'");
+
+    query
+        .eq("role", filter.role.as_deref())
+        .ends_with(
+            "email",
+            filter
+                .email_domain
+                .as_deref()
+                .map(|domain| format!("@{domain}"))
+                .as_deref(),
+        )
+        .from("created_at", filter.created_after)
+        .until("created_at", filter.created_before)
+}
@@ -0,0 +1,62 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ApiKeyScope {
+    #[serde(rename = "posts:write")]
+    PostsWrite,
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:write")]
+    UsersWrite,
+}
+
+impl Display for ApiKeyScope {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::PostsWrite => write!(f, "posts:write"),
+            Self::UsersRead => write!(f, "users:read"),
+            Self::UsersWrite => write!(f, "users:write"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.iter().any(|s| *s == scope.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateApiKeyDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[serde(default)]
+    pub scopes: Vec<ApiKeyScope>,
+
+    pub expires_at: Option<DateTime<Utc>>,
+}
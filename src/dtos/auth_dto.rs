@@ -1,4 +1,6 @@
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
 use validator::Validate;
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
@@ -30,3 +32,70 @@ pub struct RegisterDto {
     )]
     pub confirm_password: String,
 }
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct VerifyEmailDto {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct ResendVerificationDto {
+    #[validate(email(message = "Email is invalid"))]
+    pub email: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct ForgotPasswordDto {
+    #[validate(email(message = "Email is invalid"))]
+    pub email: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct ResetPasswordDto {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+
+    #[validate(length(min = 6, message = "Password must be at least 6 characters long"))]
+    pub password: String,
+
+    #[validate(
+        length(
+            min = 6,
+            message = "Confirm password must be at least 6 characters long"
+        ),
+        must_match(other = "password", message = "Passwords do not match")
+    )]
+    pub confirm_password: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct LoginMfaDto {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    pub mfa_token: String,
+
+    #[validate(length(
+        min = 6,
+        max = 16,
+        message = "Code must be between 6 and 16 characters"
+    ))]
+    pub code: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct MfaCodeDto {
+    #[validate(length(
+        min = 6,
+        max = 16,
+        message = "Code must be between 6 and 16 characters"
+    ))]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginAttempt {
+    pub key: String,
+    pub failures: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_failure_at: DateTime<Utc>,
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub user_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// who may moderate a comment: its author and the owner of the post it was left on
+#[derive(Debug, Clone, FromRow)]
+pub struct CommentOwnership {
+    pub user_id: Uuid,
+    pub post_owner_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentNode {
+    #[serde(flatten)]
+    pub comment: Comment,
+    pub replies: Vec<CommentNode>,
+}
+
+impl CommentNode {
+    // `comments` holds whole threads, replies keep the order they come in
+    pub fn build_tree(comments: Vec<Comment>) -> Vec<Self> {
+        let mut children: HashMap<Option<Uuid>, Vec<Comment>> = HashMap::new();
+        for comment in comments {
+            children.entry(comment.parent_id).or_default().push(comment);
+        }
+
+        Self::children_of(None, &mut children)
+    }
+
+    fn children_of(
+        parent_id: Option<Uuid>,
+        children: &mut HashMap<Option<Uuid>, Vec<Comment>>,
+    ) -> Vec<Self> {
+        children
+            .remove(&parent_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|comment| {
+                let replies = Self::children_of(Some(comment.id), children);
+                Self { comment, replies }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentsView {
+    #[default]
+    Tree,
+    Flat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CommentsQueryDto {
+    #[validate(range(min = 1))]
+    pub page: Option<usize>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<usize>,
+    pub view: Option<CommentsView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateCommentDto {
+    #[validate(length(
+        min = 1,
+        max = 2000,
+        message = "body must be between 1 and 2000 characters"
+    ))]
+    pub body: String,
+
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateCommentDto {
+    #[validate(length(
+        min = 1,
+        max = 2000,
+        message = "body must be between 1 and 2000 characters"
+    ))]
+    pub body: String,
+}
@@ -1,6 +1,13 @@
+pub mod api_key_dto;
 pub mod auth_dto;
+pub mod comment_dto;
+pub mod oauth_dto;
 pub mod post_dto;
+pub mod role_dto;
+pub mod session_dto;
+pub mod tag_dto;
 pub mod user_dto;
+pub mod user_token_dto;
 
 use serde::{Deserialize, Serialize};
 use validator::Validate;
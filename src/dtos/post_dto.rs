@@ -1,8 +1,24 @@
+use std::collections::BTreeMap;
+
 use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::{FromRow, Type};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::dtos::tag_dto::{MAX_TAG_LENGTH, TagMatch, normalize_tags, slugify};
+
+// only published posts are public, drafts, scheduled and archived posts are seen by their author
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "post_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    #[default]
+    Draft,
+    Scheduled,
+    Published,
+    Archived,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Type)]
 pub struct Post {
@@ -10,8 +26,38 @@ pub struct Post {
     pub user_id: Uuid,
     pub title: String,
     pub body: String,
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReactionCount {
+    pub post_id: Uuid,
+    pub kind: String,
+    pub count: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserReaction {
+    pub post_id: Uuid,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReactionSummary {
+    pub count: i32,
+    pub reacted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostResponseDto {
+    #[serde(flatten)]
+    pub post: Post,
+    pub tags: Vec<String>,
+    pub reactions: BTreeMap<String, ReactionSummary>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -21,6 +67,16 @@ pub struct CreatePostDto {
 
     #[validate(length(min = 20, message = "body must be at least 20 characters long"))]
     pub body: String,
+
+    #[validate(
+        length(max = 10, message = "a post can have at most 10 tags"),
+        custom(function = "validate_tags")
+    )]
+    pub tags: Option<Vec<String>>,
+
+    pub status: Option<PostStatus>,
+
+    pub published_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -30,4 +86,150 @@ pub struct UpdatePostDto {
 
     #[validate(length(min = 20, message = "body must be at least 20 characters long"))]
     pub body: Option<String>,
+
+    #[validate(
+        length(max = 10, message = "a post can have at most 10 tags"),
+        custom(function = "validate_tags")
+    )]
+    pub tags: Option<Vec<String>>,
+
+    pub status: Option<PostStatus>,
+
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+pub const POST_SORT_FIELDS: &[&str] = &["created_at", "updated_at", "title"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PostsQueryDto {
+    #[validate(range(min = 1))]
+    pub page: Option<usize>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub author: Option<Uuid>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    #[validate(length(
+        min = 1,
+        max = 200,
+        message = "title must be between 1 and 200 characters"
+    ))]
+    pub title: Option<String>,
+    pub status: Option<PostStatus>,
+    #[serde(default)]
+    #[validate(
+        length(max = 10, message = "at most 10 tags can be filtered on"),
+        custom(function = "validate_tag_filter")
+    )]
+    pub tag: Vec<String>,
+    pub tag_match: Option<TagMatch>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PostFilter {
+    pub author: Option<Uuid>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub title_prefix: Option<String>,
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    pub status: PostStatus,
+}
+
+impl PostFilter {
+    pub fn from_query(query: &PostsQueryDto) -> Self {
+        Self {
+            author: query.author,
+            created_after: query.created_after,
+            created_before: query.created_before,
+            title_prefix: query.title.clone(),
+            tags: normalize_tags(&query.tag),
+            tag_match: query.tag_match.unwrap_or_default(),
+            status: query.status.unwrap_or(PostStatus::Published),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SearchPostsQueryDto {
+    #[validate(length(min = 1, max = 200, message = "q must be between 1 and 200 characters"))]
+    pub q: String,
+    #[validate(range(min = 1))]
+    pub page: Option<usize>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PostSearchResult {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub rank: f32,
+    pub title_highlight: String,
+    pub body_highlight: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PostRevision {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub body: String,
+    pub editor_id: Option<Uuid>,
+    pub reverted_from: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RevisionDiffQueryDto {
+    #[validate(range(min = 1, message = "from must be at least 1"))]
+    pub from: i32,
+
+    #[validate(range(min = 1, message = "to must be at least 1"))]
+    pub to: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineChange {
+    pub tag: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub value: String,
+}
+
+// an empty `tag=` is ignored, anything else has to name a tag
+fn validate_tag_filter(tags: &[String]) -> Result<(), ValidationError> {
+    match tags
+        .iter()
+        .filter(|tag| !tag.trim().is_empty())
+        .all(|tag| is_valid_tag(tag))
+    {
+        true => Ok(()),
+        false => Err(invalid_tags()),
+    }
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    match tags.iter().all(|tag| is_valid_tag(tag)) {
+        true => Ok(()),
+        false => Err(invalid_tags()),
+    }
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    let slug = slugify(tag);
+    !slug.is_empty() && slug.chars().count() <= MAX_TAG_LENGTH
+}
+
+fn invalid_tags() -> ValidationError {
+    ValidationError::new("tags").with_message(
+        format!("tags must contain between 1 and {MAX_TAG_LENGTH} letters or digits").into(),
+    )
 }
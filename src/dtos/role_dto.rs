@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Permission {
+    #[serde(rename = "posts:create")]
+    PostsCreate,
+    #[serde(rename = "posts:update:any")]
+    PostsUpdateAny,
+    #[serde(rename = "posts:delete:any")]
+    PostsDeleteAny,
+    #[serde(rename = "posts:read:any")]
+    PostsReadAny,
+    #[serde(rename = "comments:create")]
+    CommentsCreate,
+    #[serde(rename = "comments:update:any")]
+    CommentsUpdateAny,
+    #[serde(rename = "comments:delete:any")]
+    CommentsDeleteAny,
+    #[serde(rename = "users:read")]
+    UsersRead,
+    #[serde(rename = "users:update:any")]
+    UsersUpdateAny,
+    #[serde(rename = "users:delete:any")]
+    UsersDeleteAny,
+    #[serde(rename = "users:role:update")]
+    UsersRoleUpdate,
+    #[serde(rename = "users:sessions:revoke")]
+    UsersSessionsRevoke,
+    #[serde(rename = "users:suspend")]
+    UsersSuspend,
+    #[serde(rename = "mfa:policy:update")]
+    MfaPolicyUpdate,
+    #[serde(rename = "roles:manage")]
+    RolesManage,
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::PostsCreate => write!(f, "posts:create"),
+            Self::PostsUpdateAny => write!(f, "posts:update:any"),
+            Self::PostsDeleteAny => write!(f, "posts:delete:any"),
+            Self::PostsReadAny => write!(f, "posts:read:any"),
+            Self::CommentsCreate => write!(f, "comments:create"),
+            Self::CommentsUpdateAny => write!(f, "comments:update:any"),
+            Self::CommentsDeleteAny => write!(f, "comments:delete:any"),
+            Self::UsersRead => write!(f, "users:read"),
+            Self::UsersUpdateAny => write!(f, "users:update:any"),
+            Self::UsersDeleteAny => write!(f, "users:delete:any"),
+            Self::UsersRoleUpdate => write!(f, "users:role:update"),
+            Self::UsersSessionsRevoke => write!(f, "users:sessions:revoke"),
+            Self::UsersSuspend => write!(f, "users:suspend"),
+            Self::MfaPolicyUpdate => write!(f, "mfa:policy:update"),
+            Self::RolesManage => write!(f, "roles:manage"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PermissionInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Role {
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateRoleDto {
+    #[validate(
+        length(
+            min = 2,
+            max = 50,
+            message = "name must be between 2 and 50 characters"
+        ),
+        custom(function = "validate_role_name")
+    )]
+    pub name: String,
+
+    #[validate(length(max = 255, message = "description must be at most 255 characters"))]
+    pub description: Option<String>,
+
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateRoleDto {
+    #[validate(length(max = 255, message = "description must be at most 255 characters"))]
+    pub description: Option<String>,
+
+    pub permissions: Option<Vec<Permission>>,
+}
+
+fn validate_role_name(name: &str) -> Result<(), ValidationError> {
+    if name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Ok(());
+    }
+
+    Err(ValidationError::new("role_name")
+        .with_message("name may only contain lowercase letters, digits, `-` and `_`".into()))
+}
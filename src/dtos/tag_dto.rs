@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+pub const MAX_TAG_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tag {
+    pub slug: String,
+    pub post_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PostTag {
+    pub post_id: Uuid,
+    pub slug: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+// `Rust Lang` and `rust-lang` end up as the same tag
+pub fn slugify(tag: &str) -> String {
+    let mut slug = String::new();
+
+    for c in tag.trim().to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    slug.trim_end_matches('-').to_string()
+}
+
+// slugs in their original order without duplicates, tags without any letter or digit are dropped
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut slugs: Vec<String> = Vec::with_capacity(tags.len());
+
+    for slug in tags.iter().map(|tag| slugify(tag)) {
+        if !slug.is_empty() && !slugs.contains(&slug) {
+            slugs.push(slug);
+        }
+    }
+
+    slugs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn slugify_lowercases_and_joins_words_with_dashes() {
+        assert_eq!(slugify("Rust Lang"), "rust-lang");
+        assert_eq!(slugify("rust-lang"), "rust-lang");
+        assert_eq!(slugify("  --Rust   Lang!! "), "rust-lang");
+        assert_eq!(slugify("Async/Await"), "async-await");
+    }
+
+    #[test]
+    fn slugify_keeps_non_ascii_letters() {
+        assert_eq!(slugify("Café Crème"), "café-crème");
+    }
+
+    #[test]
+    fn slugify_drops_tags_without_letters_or_digits() {
+        assert_eq!(slugify(""), "");
+        assert_eq!(slugify(" #!- "), "");
+    }
+
+    #[test]
+    fn normalize_tags_keeps_the_first_of_each_slug_in_order() {
+        assert_eq!(
+            normalize_tags(&tags(&[
+                "Web",
+                "Rust Lang",
+                "---",
+                "rust-lang",
+                "web",
+                "axum"
+            ])),
+            tags(&["web", "rust-lang", "axum"])
+        );
+        assert!(normalize_tags(&[]).is_empty());
+    }
+}
@@ -4,20 +4,13 @@ use sqlx::prelude::{FromRow, Type};
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq, Copy)]
-#[sqlx(type_name = "user_role", rename_all = "lowercase")]
-pub enum UserRole {
-    Admin,
-    User,
-}
-
-impl UserRole {
-    pub fn to_str(&self) -> &str {
-        match self {
-            Self::Admin => "admin",
-            Self::User => "user",
-        }
-    }
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq)]
+#[sqlx(type_name = "user_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Active,
+    Suspended,
+    Deactivated,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, Type)]
@@ -26,17 +19,39 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub password: String,
-    pub role: UserRole,
+    pub role: String,
+    pub token_version: i32,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<DateTime<Utc>>,
+    pub status: UserStatus,
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub suspension_reason: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub deleted_by: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl User {
+    pub fn is_suspended(&self) -> bool {
+        self.status == UserStatus::Suspended
+            && self
+                .suspended_until
+                .is_none_or(|suspended_until| suspended_until > Utc::now())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponseDto {
     pub id: Uuid,
     pub name: String,
     pub email: String,
-    pub role: UserRole,
+    pub role: String,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub mfa_enabled: bool,
+    pub status: UserStatus,
+    pub suspended_until: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -47,7 +62,11 @@ impl UserResponseDto {
             id: user.id,
             name: user.name.clone(),
             email: user.email.clone(),
-            role: user.role,
+            role: user.role.clone(),
+            email_verified_at: user.email_verified_at,
+            mfa_enabled: user.totp_enabled_at.is_some(),
+            status: user.status,
+            suspended_until: user.suspended_until,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
@@ -60,9 +79,54 @@ pub struct UsersResponseDtoList {
 }
 
 impl UsersResponseDtoList {
-    pub fn from_users(users: &Vec<User>) -> Self {
+    pub fn from_users(users: &[User]) -> Self {
         Self {
-            users_list: users.into_iter().map(UserResponseDto::from_user).collect(),
+            users_list: users.iter().map(UserResponseDto::from_user).collect(),
+        }
+    }
+}
+
+pub const USER_SORT_FIELDS: &[&str] = &["created_at", "updated_at", "name", "email"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UsersQueryDto {
+    #[validate(range(min = 1))]
+    pub page: Option<usize>,
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "role must be between 1 and 50 characters"
+    ))]
+    pub role: Option<String>,
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "email_domain must be between 1 and 255 characters"
+    ))]
+    pub email_domain: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UserFilter {
+    pub role: Option<String>,
+    pub email_domain: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl UserFilter {
+    pub fn from_query(query: &UsersQueryDto) -> Self {
+        Self {
+            role: query.role.clone(),
+            email_domain: query.email_domain.clone(),
+            created_after: query.created_after,
+            created_before: query.created_before,
         }
     }
 }
@@ -79,7 +143,27 @@ pub struct UpdateUserDto {
     pub password: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct UpdateUserRoleDto {
-    pub role: UserRole,
+    #[validate(length(min = 1, message = "role is required"))]
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateMfaPolicyDto {
+    #[validate(length(min = 1, message = "role is required"))]
+    pub role: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct SuspendUserDto {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "reason must be between 1 and 255 characters"
+    ))]
+    pub reason: String,
+
+    pub until: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,23 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::{FromRow, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq, Copy)]
+#[sqlx(type_name = "user_token_kind", rename_all = "snake_case")]
+pub enum UserTokenKind {
+    EmailVerification,
+    PasswordReset,
+    MfaPending,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: UserTokenKind,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -5,8 +5,8 @@ use std::{
 
 use axum::{
     Json,
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    http::{HeaderName, HeaderValue, StatusCode},
+    response::{AppendHeaders, IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -38,11 +38,15 @@ pub enum ErrorMessage {
     PermissionDenied,
     UserNotAuthenticated,
     InvalidHashForamt,
+    MailDeliveryFailed,
+    InvalidTotpSecret,
+    OAuthProviderError,
+    InvalidIdToken,
 }
 
-impl ToString for ErrorMessage {
-    fn to_string(&self) -> String {
-        match self {
+impl Display for ErrorMessage {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let message = match self {
             Self::EmptyPassword => "Password cannot be empty".to_string(),
             Self::ExceededMaxPaasswordLength(length) => {
                 format!("Password exceeds maximum length of {length}")
@@ -58,7 +62,13 @@ impl ToString for ErrorMessage {
             Self::PermissionDenied => "Permission denied".to_string(),
             Self::UserNotAuthenticated => "User not authenticated".to_string(),
             Self::InvalidHashForamt => "Invalid hash format".to_string(),
-        }
+            Self::MailDeliveryFailed => "Failed to deliver mail".to_string(),
+            Self::InvalidTotpSecret => "Invalid TOTP secret".to_string(),
+            Self::OAuthProviderError => "OAuth provider request failed".to_string(),
+            Self::InvalidIdToken => "OAuth provider returned an invalid id token".to_string(),
+        };
+
+        write!(f, "{message}")
     }
 }
 
@@ -66,6 +76,7 @@ impl ToString for ErrorMessage {
 pub struct HttpError {
     pub status: StatusCode,
     pub message: String,
+    pub headers: Vec<(HeaderName, HeaderValue)>,
 }
 
 impl HttpError {
@@ -73,7 +84,15 @@ impl HttpError {
         Self {
             status,
             message: message.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: HeaderName, value: impl ToString) -> Self {
+        if let Ok(value) = HeaderValue::from_str(&value.to_string()) {
+            self.headers.push((name, value));
         }
+        self
     }
 
     pub fn not_found(message: impl Into<String>) -> Self {
@@ -92,23 +111,40 @@ impl HttpError {
         Self::new(StatusCode::CONFLICT, message)
     }
 
+    pub fn precondition_failed(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::PRECONDITION_FAILED, message)
+    }
+
     pub fn unauthorized(message: impl Into<String>) -> Self {
         Self::new(StatusCode::UNAUTHORIZED, message)
     }
 
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, message)
+    }
+
+    pub fn too_many_requests(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::TOO_MANY_REQUESTS, message)
+    }
+
     pub fn into_http_response(self) -> Response {
         let error_response = ErrorResponse {
             status: self.status.clone().to_string(),
             message: self.message.clone(),
         };
-        (self.status, Json(error_response)).into_response()
+        (
+            self.status,
+            AppendHeaders(self.headers),
+            Json(error_response),
+        )
+            .into_response()
     }
 }
 
 impl IntoResponse for HttpError {
     fn into_response(self) -> Response {
         let body = Json(json!({ "error": self.message }));
-        (self.status, body).into_response()
+        (self.status, AppendHeaders(self.headers), body).into_response()
     }
 }
 
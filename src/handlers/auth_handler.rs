@@ -1,37 +1,110 @@
 use axum::{
     Extension, Json, Router,
+    extract::{Path, Query},
     http::{HeaderMap, StatusCode, header},
+    middleware,
     response::Response,
     routing::{delete, get, post},
 };
-use axum_extra::extract::cookie::Cookie;
+use axum_extra::extract::{CookieJar, cookie::Cookie};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    dtos::auth_dto::{LoginDto, RegisterDto},
+    AppState,
+    dtos::{
+        auth_dto::{
+            ForgotPasswordDto, LoginDto, LoginMfaDto, MfaCodeDto, RegisterDto,
+            ResendVerificationDto, ResetPasswordDto, VerifyEmailDto,
+        },
+        oauth_dto::OAuthCallbackDto,
+        user_dto::User,
+    },
     error::HttpError,
-    services::auth_service::AuthService,
+    middlewares::{Middleware, auth_guard::AuthGuard},
+    services::auth_service::{AuthService, OAUTH_STATE_COOKIE},
+    utils::client_info::ClientInfo,
 };
 
 #[derive(Debug, Clone)]
-pub struct AuthHandler {}
+pub struct AuthHandler {
+    app_state: AppState,
+}
 
 impl AuthHandler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(app_state: AppState) -> Self {
+        Self { app_state }
     }
 
     pub fn router(&self, auth_service: AuthService) -> Router {
         Router::new()
             .route("/refresh", get(Self::refresh))
             .route("/login", post(Self::login))
+            .route("/login/mfa", post(Self::login_mfa))
             .route("/register", post(Self::register))
             .route("/logout", delete(Self::logout))
+            .route(
+                "/oauth/{provider}/start",
+                get(Self::oauth_start).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .optional()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
+            .route("/oauth/{provider}/callback", get(Self::oauth_callback))
+            .route(
+                "/verify-email",
+                get(Self::verify_email_link).post(Self::verify_email),
+            )
+            .route("/resend-verification", post(Self::resend_verification))
+            .route("/forgot-password", post(Self::forgot_password))
+            .route("/reset-password", post(Self::reset_password))
+            .route(
+                "/sessions",
+                get(Self::get_sessions).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new().validate_request(state, req, next).await
+                })),
+            )
+            .route(
+                "/sessions/{id}",
+                delete(Self::revoke_session).layer(middleware::from_fn(
+                    async |state, req, next| {
+                        AuthGuard::new().validate_request(state, req, next).await
+                    },
+                )),
+            )
+            .route(
+                "/mfa/enroll",
+                post(Self::enroll_mfa).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .allow_mfa_setup()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
+            .route(
+                "/mfa/confirm",
+                post(Self::confirm_mfa).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .allow_mfa_setup()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
+            .route(
+                "/mfa",
+                delete(Self::disable_mfa).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new().validate_request(state, req, next).await
+                })),
+            )
+            .layer(Extension(self.app_state.clone()))
             .layer(Extension(auth_service))
     }
 
     async fn refresh(
         Extension(auth_service): Extension<AuthService>,
+        client: ClientInfo,
         headers: HeaderMap,
     ) -> Result<Response, HttpError> {
         match headers.get(header::COOKIE) {
@@ -39,7 +112,7 @@ impl AuthHandler {
                 let cookie = Cookie::parse(cookie_header.to_str().unwrap_or(""))
                     .map_err(|_| HttpError::bad_request("invalid cookie"))?;
 
-                auth_service.refresh(cookie)
+                auth_service.refresh(cookie, client).await
             }
             None => Err(HttpError::unauthorized("missing authentication cookie")),
         }
@@ -57,12 +130,131 @@ impl AuthHandler {
 
     async fn login(
         Extension(auth_service): Extension<AuthService>,
+        client: ClientInfo,
         Json(body): Json<LoginDto>,
     ) -> Result<Response, HttpError> {
         body.validate()
             .map_err(|e| HttpError::bad_request(e.to_string()))?;
 
-        auth_service.login(body).await
+        auth_service.login(body, client).await
+    }
+
+    async fn login_mfa(
+        Extension(auth_service): Extension<AuthService>,
+        client: ClientInfo,
+        Json(body): Json<LoginMfaDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.login_mfa(body, client).await
+    }
+
+    async fn enroll_mfa(
+        Extension(auth_service): Extension<AuthService>,
+        Extension(user): Extension<User>,
+    ) -> Result<Response, HttpError> {
+        auth_service.enroll_mfa(&user).await
+    }
+
+    async fn confirm_mfa(
+        Extension(auth_service): Extension<AuthService>,
+        Extension(user): Extension<User>,
+        Json(body): Json<MfaCodeDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.confirm_mfa(&user, body).await
+    }
+
+    async fn disable_mfa(
+        Extension(auth_service): Extension<AuthService>,
+        Extension(user): Extension<User>,
+        Json(body): Json<MfaCodeDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.disable_mfa(&user, body).await
+    }
+
+    async fn oauth_start(
+        Extension(auth_service): Extension<AuthService>,
+        user: Option<Extension<User>>,
+        Path(provider): Path<String>,
+    ) -> Result<Response, HttpError> {
+        auth_service
+            .oauth_start(&provider, user.map(|Extension(user)| user))
+            .await
+    }
+
+    async fn oauth_callback(
+        Extension(auth_service): Extension<AuthService>,
+        client: ClientInfo,
+        jar: CookieJar,
+        Path(provider): Path<String>,
+        Query(query_params): Query<OAuthCallbackDto>,
+    ) -> Result<Response, HttpError> {
+        let state_cookie = jar
+            .get(OAUTH_STATE_COOKIE)
+            .map(|cookie| cookie.value().to_string());
+
+        auth_service
+            .oauth_callback(&provider, query_params, state_cookie, client)
+            .await
+    }
+
+    async fn verify_email(
+        Extension(auth_service): Extension<AuthService>,
+        Json(body): Json<VerifyEmailDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.verify_email(body).await
+    }
+
+    // the link in the verification mail
+    async fn verify_email_link(
+        Extension(auth_service): Extension<AuthService>,
+        Query(query_params): Query<VerifyEmailDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.verify_email(query_params).await
+    }
+
+    async fn resend_verification(
+        Extension(auth_service): Extension<AuthService>,
+        Json(body): Json<ResendVerificationDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.resend_verification(body).await
+    }
+
+    async fn forgot_password(
+        Extension(auth_service): Extension<AuthService>,
+        Json(body): Json<ForgotPasswordDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.forgot_password(body).await
+    }
+
+    async fn reset_password(
+        Extension(auth_service): Extension<AuthService>,
+        Json(body): Json<ResetPasswordDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        auth_service.reset_password(body).await
     }
 
     async fn logout(
@@ -74,9 +266,27 @@ impl AuthHandler {
                 let cookie = Cookie::parse(cookie_header.to_str().unwrap_or(""))
                     .map_err(|_| HttpError::bad_request("invalid cookie"))?;
 
-                auth_service.logout(cookie)
+                auth_service.logout(cookie).await
             }
             None => Err(HttpError::unauthorized("missing authentication cookie")),
         }
     }
+
+    async fn get_sessions(
+        Extension(auth_service): Extension<AuthService>,
+        Extension(user): Extension<User>,
+    ) -> Result<Response, HttpError> {
+        auth_service.get_sessions(user.id).await
+    }
+
+    async fn revoke_session(
+        Extension(auth_service): Extension<AuthService>,
+        Extension(user): Extension<User>,
+        Path(id): Path<String>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        auth_service.revoke_session(user.id, uuid).await
+    }
 }
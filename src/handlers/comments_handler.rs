@@ -0,0 +1,186 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query},
+    middleware,
+    response::Response,
+    routing::{delete, get, patch, post},
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    db::{DBClient, comments_db::CommentExt},
+    dtos::{
+        api_key_dto::ApiKeyScope,
+        comment_dto::{CommentsQueryDto, CreateCommentDto, UpdateCommentDto},
+        role_dto::Permission,
+        user_dto::User,
+    },
+    error::HttpError,
+    middlewares::{
+        Middleware, auth_guard::AuthGuard, owner_guard::OwnerGuard,
+        permission_guard::PermissionGuard, verified_guard::VerifiedGuard,
+    },
+    services::comments_service::CommentsService,
+};
+
+#[derive(Debug, Clone)]
+pub struct CommentsHandler {
+    app_state: AppState,
+}
+
+impl CommentsHandler {
+    pub fn new(app_state: AppState) -> Self {
+        Self { app_state }
+    }
+
+    pub fn router(&self, comments_service: CommentsService) -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(Self::get_comments).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .optional()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
+            .route(
+                "/",
+                post(Self::create_comment)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        PermissionGuard::new(Permission::CommentsCreate)
+                            .validate_request(user, req, next)
+                            .await
+                    }))
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        VerifiedGuard::new().validate_request(user, req, next).await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{comment_id}",
+                patch(Self::update_comment)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_comment(id).await,
+                            Permission::CommentsUpdateAny,
+                        )
+                        .with_param("comment_id")
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{comment_id}",
+                delete(Self::delete_comment)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| {
+                                db_client.get_comment_ownership(id).await
+                            },
+                            Permission::CommentsDeleteAny,
+                        )
+                        .with_param("comment_id")
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .layer(Extension(self.app_state.clone()))
+            .layer(Extension(comments_service))
+    }
+
+    async fn get_comments(
+        Extension(comments_service): Extension<CommentsService>,
+        user: Option<Extension<User>>,
+        Path(id): Path<String>,
+        Query(query_params): Query<CommentsQueryDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        let page = query_params.page.unwrap_or(1);
+        let limit = query_params.limit.unwrap_or(10);
+
+        comments_service
+            .get_comments(
+                uuid,
+                user.map(|Extension(user)| user),
+                query_params.view.unwrap_or_default(),
+                page,
+                limit,
+            )
+            .await
+    }
+
+    async fn create_comment(
+        Extension(comments_service): Extension<CommentsService>,
+        Extension(user): Extension<User>,
+        Path(id): Path<String>,
+        Json(body): Json<CreateCommentDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        comments_service.create_comment(uuid, &user, body).await
+    }
+
+    async fn update_comment(
+        Extension(comments_service): Extension<CommentsService>,
+        Path((id, comment_id)): Path<(String, String)>,
+        Json(body): Json<UpdateCommentDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let (uuid, comment_uuid) = Self::parse_comment_params(&id, &comment_id)?;
+
+        comments_service
+            .update_comment(uuid, comment_uuid, body.body)
+            .await
+    }
+
+    async fn delete_comment(
+        Extension(comments_service): Extension<CommentsService>,
+        Path((id, comment_id)): Path<(String, String)>,
+    ) -> Result<Response, HttpError> {
+        let (uuid, comment_uuid) = Self::parse_comment_params(&id, &comment_id)?;
+
+        comments_service.delete_comment(uuid, comment_uuid).await
+    }
+
+    fn parse_comment_params(id: &str, comment_id: &str) -> Result<(Uuid, Uuid), HttpError> {
+        let uuid = Uuid::parse_str(id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        let comment_uuid = Uuid::parse_str(comment_id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `comment_id` param"))?;
+
+        Ok((uuid, comment_uuid))
+    }
+}
@@ -1,13 +1,18 @@
 pub mod auth_handler;
+pub mod comments_handler;
 pub mod posts_handler;
+pub mod roles_handler;
 pub mod root_handler;
+pub mod tags_handler;
 pub mod users_handler;
+pub mod well_known_handler;
 
 use crate::{
     AppState,
     handlers::{
-        auth_handler::AuthHandler, posts_handler::PostsHandler, root_handler::RootHandler,
-        users_handler::UsersHandler,
+        auth_handler::AuthHandler, comments_handler::CommentsHandler, posts_handler::PostsHandler,
+        roles_handler::RolesHandler, root_handler::RootHandler, tags_handler::TagsHandler,
+        users_handler::UsersHandler, well_known_handler::WellKnownHandler,
     },
 };
 
@@ -17,15 +22,23 @@ pub struct Handlers {
     pub auth_handler: AuthHandler,
     pub users_handler: UsersHandler,
     pub posts_handler: PostsHandler,
+    pub comments_handler: CommentsHandler,
+    pub roles_handler: RolesHandler,
+    pub tags_handler: TagsHandler,
+    pub well_known_handler: WellKnownHandler,
 }
 
 impl Handlers {
     pub fn new(app_state: AppState) -> Self {
         Self {
             root_handler: RootHandler::new(),
-            auth_handler: AuthHandler::new(),
+            auth_handler: AuthHandler::new(app_state.clone()),
             users_handler: UsersHandler::new(app_state.clone()),
-            posts_handler: PostsHandler::new(app_state),
+            posts_handler: PostsHandler::new(app_state.clone()),
+            comments_handler: CommentsHandler::new(app_state.clone()),
+            roles_handler: RolesHandler::new(app_state.clone()),
+            tags_handler: TagsHandler::new(),
+            well_known_handler: WellKnownHandler::new(app_state),
         }
     }
 }
@@ -1,23 +1,36 @@
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query},
+    http::HeaderMap,
     middleware,
     response::Response,
-    routing::{delete, get, patch, post},
+    routing::{delete, get, patch, post, put},
 };
+// repeated `tag` params are only parsed by the axum_extra query extractor
+use axum_extra::extract::Query as MultiQuery;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
     AppState,
+    db::{DBClient, posts_db::PostExt},
     dtos::{
         QueryRangeDto,
-        post_dto::{CreatePostDto, UpdatePostDto},
-        user_dto::{User, UserRole},
+        api_key_dto::ApiKeyScope,
+        post_dto::{
+            CreatePostDto, POST_SORT_FIELDS, PostFilter, PostsQueryDto, RevisionDiffQueryDto,
+            SearchPostsQueryDto, UpdatePostDto,
+        },
+        role_dto::Permission,
+        user_dto::User,
     },
     error::HttpError,
-    middlewares::{Middleware, auth_guard::AuthGuard, self_guard::SelfGuard},
+    middlewares::{
+        Middleware, auth_guard::AuthGuard, owner_guard::OwnerGuard,
+        permission_guard::PermissionGuard, verified_guard::VerifiedGuard,
+    },
     services::posts_service::PostsService,
+    utils::pagination::ListParams,
 };
 
 #[derive(Debug, Clone)]
@@ -32,57 +45,216 @@ impl PostsHandler {
 
     pub fn router(&self, posts_service: PostsService) -> Router {
         Router::new()
-            .route("/user/{id}", get(Self::get_posts_by_user_id))
-            .route("/{id}", get(Self::get_post))
-            .route("/", get(Self::get_posts))
+            .route(
+                "/user/{id}",
+                get(Self::get_posts_by_user_id).layer(middleware::from_fn(
+                    async |state, req, next| {
+                        AuthGuard::new()
+                            .optional()
+                            .validate_request(state, req, next)
+                            .await
+                    },
+                )),
+            )
+            .route(
+                "/{id}",
+                get(Self::get_post).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .optional()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
             .route(
                 "/",
-                post(Self::create_post).layer(middleware::from_fn(async |state, req, next| {
+                get(Self::get_posts).layer(middleware::from_fn(async |state, req, next| {
+                    AuthGuard::new()
+                        .optional()
+                        .validate_request(state, req, next)
+                        .await
+                })),
+            )
+            .route("/search", get(Self::search_posts))
+            .route(
+                "/trash",
+                get(Self::get_trash).layer(middleware::from_fn(async |state, req, next| {
                     AuthGuard::new().validate_request(state, req, next).await
                 })),
             )
             .route(
-                "/{id}",
-                patch(Self::update_post)
+                "/",
+                post(Self::create_post)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        SelfGuard::new(vec![UserRole::Admin])
+                        PermissionGuard::new(Permission::PostsCreate)
                             .validate_request(user, req, next)
                             .await
                     }))
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        VerifiedGuard::new().validate_request(user, req, next).await
+                    }))
                     .layer(middleware::from_fn(async |state, req, next| {
-                        AuthGuard::new().validate_request(state, req, next).await
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{id}",
+                patch(Self::update_post)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsUpdateAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
                     })),
             )
             .route(
                 "/{id}",
                 delete(Self::delete_post)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        SelfGuard::new(vec![UserRole::Admin])
-                            .validate_request(user, req, next)
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsDeleteAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
                             .await
+                    })),
+            )
+            .route(
+                "/{id}/restore",
+                post(Self::restore_post)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| {
+                                db_client.get_deleted_post_by_id(id).await
+                            },
+                            Permission::PostsDeleteAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{id}/revisions",
+                get(Self::get_post_revisions)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsUpdateAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
                     }))
                     .layer(middleware::from_fn(async |state, req, next| {
                         AuthGuard::new().validate_request(state, req, next).await
                     })),
             )
+            .route(
+                "/{id}/revisions/diff",
+                get(Self::diff_post_revisions)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsUpdateAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new().validate_request(state, req, next).await
+                    })),
+            )
+            .route(
+                "/{id}/revisions/{rev}",
+                get(Self::get_post_revision)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsUpdateAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new().validate_request(state, req, next).await
+                    })),
+            )
+            .route(
+                "/{id}/revisions/{rev}/revert",
+                post(Self::revert_post)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        OwnerGuard::new(
+                            async |db_client: DBClient, id| db_client.get_post_by_id(id).await,
+                            Permission::PostsUpdateAny,
+                        )
+                        .validate_request(user, req, next)
+                        .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{id}/reactions/{kind}",
+                put(Self::add_reaction)
+                    .delete(Self::remove_reaction)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        VerifiedGuard::new().validate_request(user, req, next).await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::PostsWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
             .layer(Extension(self.app_state.clone()))
             .layer(Extension(posts_service))
     }
 
     async fn get_post(
         Extension(posts_service): Extension<PostsService>,
+        user: Option<Extension<User>>,
         Path(id): Path<String>,
+        headers: HeaderMap,
     ) -> Result<Response, HttpError> {
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
-        posts_service.get_post(uuid).await
+        posts_service
+            .get_post(uuid, user.map(|Extension(user)| user), &headers)
+            .await
     }
 
     async fn get_posts_by_user_id(
         Extension(posts_service): Extension<PostsService>,
+        user: Option<Extension<User>>,
         Path(id): Path<String>,
-        Query(query_params): Query<QueryRangeDto>,
+        MultiQuery(query_params): MultiQuery<PostsQueryDto>,
     ) -> Result<Response, HttpError> {
         query_params
             .validate()
@@ -91,15 +263,41 @@ impl PostsHandler {
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
-        let page = query_params.page.unwrap_or(1);
-        let limit = query_params.limit.unwrap_or(10);
+        let params = Self::list_params(&query_params)?;
 
-        posts_service.get_posts_by_user_id(uuid, page, limit).await
+        posts_service
+            .get_posts_by_user_id(
+                uuid,
+                user.map(|Extension(user)| user),
+                PostFilter::from_query(&query_params),
+                params,
+            )
+            .await
     }
 
     async fn get_posts(
         Extension(posts_service): Extension<PostsService>,
-        Query(query_params): Query<QueryRangeDto>,
+        user: Option<Extension<User>>,
+        MultiQuery(query_params): MultiQuery<PostsQueryDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let params = Self::list_params(&query_params)?;
+
+        posts_service
+            .get_posts(
+                user.map(|Extension(user)| user),
+                PostFilter::from_query(&query_params),
+                params,
+            )
+            .await
+    }
+
+    async fn search_posts(
+        Extension(posts_service): Extension<PostsService>,
+        Query(query_params): Query<SearchPostsQueryDto>,
     ) -> Result<Response, HttpError> {
         query_params
             .validate()
@@ -108,7 +306,9 @@ impl PostsHandler {
         let page = query_params.page.unwrap_or(1);
         let limit = query_params.limit.unwrap_or(10);
 
-        posts_service.get_posts(page, limit).await
+        posts_service
+            .search_posts(query_params.q.trim(), page, limit)
+            .await
     }
 
     async fn create_post(
@@ -119,14 +319,14 @@ impl PostsHandler {
         post.validate()
             .map_err(|e| HttpError::bad_request(e.to_string()))?;
 
-        posts_service
-            .create_post(user.id, post.title, post.body)
-            .await
+        posts_service.create_post(user.id, post).await
     }
 
     async fn update_post(
         Extension(posts_service): Extension<PostsService>,
+        Extension(user): Extension<User>,
         Path(id): Path<String>,
+        headers: HeaderMap,
         Json(post): Json<UpdatePostDto>,
     ) -> Result<Response, HttpError> {
         let uuid = Uuid::parse_str(&id)
@@ -135,7 +335,9 @@ impl PostsHandler {
         post.validate()
             .map_err(|e| HttpError::bad_request(e.to_string()))?;
 
-        posts_service.update_post(uuid, post.title, post.body).await
+        posts_service
+            .update_post(uuid, user.id, post, &headers)
+            .await
     }
 
     async fn delete_post(
@@ -147,4 +349,126 @@ impl PostsHandler {
 
         posts_service.delete_post(uuid).await
     }
+
+    async fn get_trash(
+        Extension(posts_service): Extension<PostsService>,
+        Extension(user): Extension<User>,
+        Query(query_params): Query<QueryRangeDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let page = query_params.page.unwrap_or(1);
+        let limit = query_params.limit.unwrap_or(10);
+
+        posts_service.get_trash(&user, page, limit).await
+    }
+
+    async fn restore_post(
+        Extension(posts_service): Extension<PostsService>,
+        Path(id): Path<String>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        posts_service.restore_post(uuid).await
+    }
+
+    async fn get_post_revisions(
+        Extension(posts_service): Extension<PostsService>,
+        Path(id): Path<String>,
+        Query(query_params): Query<QueryRangeDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        let page = query_params.page.unwrap_or(1);
+        let limit = query_params.limit.unwrap_or(10);
+
+        posts_service.get_post_revisions(uuid, page, limit).await
+    }
+
+    async fn get_post_revision(
+        Extension(posts_service): Extension<PostsService>,
+        Path((id, rev)): Path<(String, String)>,
+    ) -> Result<Response, HttpError> {
+        let (uuid, revision) = Self::parse_revision_params(&id, &rev)?;
+
+        posts_service.get_post_revision(uuid, revision).await
+    }
+
+    async fn diff_post_revisions(
+        Extension(posts_service): Extension<PostsService>,
+        Path(id): Path<String>,
+        Query(query_params): Query<RevisionDiffQueryDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        posts_service
+            .diff_post_revisions(uuid, query_params.from, query_params.to)
+            .await
+    }
+
+    async fn revert_post(
+        Extension(posts_service): Extension<PostsService>,
+        Extension(user): Extension<User>,
+        Path((id, rev)): Path<(String, String)>,
+    ) -> Result<Response, HttpError> {
+        let (uuid, revision) = Self::parse_revision_params(&id, &rev)?;
+
+        posts_service.revert_post(uuid, user.id, revision).await
+    }
+
+    async fn add_reaction(
+        Extension(posts_service): Extension<PostsService>,
+        Extension(user): Extension<User>,
+        Path((id, kind)): Path<(String, String)>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        posts_service.add_reaction(uuid, &user, &kind).await
+    }
+
+    async fn remove_reaction(
+        Extension(posts_service): Extension<PostsService>,
+        Extension(user): Extension<User>,
+        Path((id, kind)): Path<(String, String)>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        posts_service.remove_reaction(uuid, &user, &kind).await
+    }
+
+    fn list_params(query_params: &PostsQueryDto) -> Result<ListParams, HttpError> {
+        ListParams::new(
+            query_params.page,
+            query_params.limit,
+            query_params.cursor.as_deref(),
+            query_params.sort.as_deref(),
+            POST_SORT_FIELDS,
+        )
+    }
+
+    fn parse_revision_params(id: &str, rev: &str) -> Result<(Uuid, i32), HttpError> {
+        let uuid = Uuid::parse_str(id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        let revision = rev
+            .parse()
+            .map_err(|_| HttpError::bad_request("Invalid revision number for `rev` param"))?;
+
+        Ok((uuid, revision))
+    }
 }
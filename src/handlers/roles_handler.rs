@@ -0,0 +1,84 @@
+use axum::{
+    Extension, Json, Router,
+    extract::Path,
+    middleware,
+    response::Response,
+    routing::{get, put},
+};
+use validator::Validate;
+
+use crate::{
+    AppState,
+    dtos::role_dto::{CreateRoleDto, Permission, UpdateRoleDto},
+    error::HttpError,
+    middlewares::{Middleware, auth_guard::AuthGuard, permission_guard::PermissionGuard},
+    services::roles_service::RolesService,
+};
+
+#[derive(Debug, Clone)]
+pub struct RolesHandler {
+    app_state: AppState,
+}
+
+impl RolesHandler {
+    pub fn new(app_state: AppState) -> Self {
+        Self { app_state }
+    }
+
+    pub fn router(&self, roles_service: RolesService) -> Router {
+        Router::new()
+            .route("/", get(Self::get_roles).post(Self::create_role))
+            .route("/permissions", get(Self::get_permissions))
+            .route("/{name}", put(Self::update_role).delete(Self::delete_role))
+            .layer(middleware::from_fn(async |user, req, next| {
+                PermissionGuard::new(Permission::RolesManage)
+                    .validate_request(user, req, next)
+                    .await
+            }))
+            .layer(middleware::from_fn(async |state, req, next| {
+                AuthGuard::new().validate_request(state, req, next).await
+            }))
+            .layer(Extension(self.app_state.clone()))
+            .layer(Extension(roles_service))
+    }
+
+    async fn get_roles(
+        Extension(roles_service): Extension<RolesService>,
+    ) -> Result<Response, HttpError> {
+        roles_service.get_roles().await
+    }
+
+    async fn get_permissions(
+        Extension(roles_service): Extension<RolesService>,
+    ) -> Result<Response, HttpError> {
+        roles_service.get_permissions().await
+    }
+
+    async fn create_role(
+        Extension(roles_service): Extension<RolesService>,
+        Json(body): Json<CreateRoleDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        roles_service.create_role(body).await
+    }
+
+    async fn update_role(
+        Extension(roles_service): Extension<RolesService>,
+        Path(name): Path<String>,
+        Json(body): Json<UpdateRoleDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        roles_service.update_role(&name, body).await
+    }
+
+    async fn delete_role(
+        Extension(roles_service): Extension<RolesService>,
+        Path(name): Path<String>,
+    ) -> Result<Response, HttpError> {
+        roles_service.delete_role(&name).await
+    }
+}
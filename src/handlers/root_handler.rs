@@ -6,7 +6,7 @@ use axum::{
 };
 use serde_json::json;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RootHandler {}
 
 impl RootHandler {
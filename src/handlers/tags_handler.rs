@@ -0,0 +1,33 @@
+use axum::{Extension, Router, extract::Query, response::Response, routing::get};
+use validator::Validate;
+
+use crate::{dtos::QueryRangeDto, error::HttpError, services::tags_service::TagsService};
+
+#[derive(Debug, Clone, Default)]
+pub struct TagsHandler {}
+
+impl TagsHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn router(&self, tags_service: TagsService) -> Router {
+        Router::new()
+            .route("/", get(Self::get_tags))
+            .layer(Extension(tags_service))
+    }
+
+    async fn get_tags(
+        Extension(tags_service): Extension<TagsService>,
+        Query(query_params): Query<QueryRangeDto>,
+    ) -> Result<Response, HttpError> {
+        query_params
+            .validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let page = query_params.page.unwrap_or(1);
+        let limit = query_params.limit.unwrap_or(10);
+
+        tags_service.get_tags(page, limit).await
+    }
+}
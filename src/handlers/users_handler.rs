@@ -1,9 +1,10 @@
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query},
+    http::HeaderMap,
     middleware,
     response::Response,
-    routing::{delete, get, patch, put},
+    routing::{delete, get, patch, post, put},
 };
 use uuid::Uuid;
 use validator::Validate;
@@ -11,14 +12,19 @@ use validator::Validate;
 use crate::{
     AppState,
     dtos::{
-        QueryRangeDto,
-        user_dto::{UpdateUserDto, UpdateUserRoleDto, UserRole},
+        api_key_dto::{ApiKeyScope, CreateApiKeyDto},
+        role_dto::Permission,
+        user_dto::{
+            SuspendUserDto, USER_SORT_FIELDS, UpdateMfaPolicyDto, UpdateUserDto, UpdateUserRoleDto,
+            User, UserFilter, UsersQueryDto,
+        },
     },
     error::HttpError,
     middlewares::{
-        Middleware, auth_guard::AuthGuard, roles_guard::RolesGuard, self_guard::SelfGuard,
+        Middleware, auth_guard::AuthGuard, permission_guard::PermissionGuard, self_guard::SelfGuard,
     },
     services::users_service::UsersService,
+    utils::pagination::ListParams,
 };
 
 #[derive(Debug, Clone)]
@@ -38,31 +44,68 @@ impl UsersHandler {
                 "/",
                 get(Self::get_users)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        RolesGuard::new(vec![UserRole::Admin])
+                        PermissionGuard::new(Permission::UsersRead)
                             .validate_request(user, req, next)
                             .await
                     }))
                     .layer(middleware::from_fn(async |state, req, next| {
-                        AuthGuard::new().validate_request(state, req, next).await
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::UsersRead)
+                            .validate_request(state, req, next)
+                            .await
                     })),
             )
             .route(
                 "/{id}",
                 patch(Self::update_user)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        SelfGuard::new(vec![UserRole::Admin])
+                        SelfGuard::new(Permission::UsersUpdateAny)
                             .validate_request(user, req, next)
                             .await
                     }))
                     .layer(middleware::from_fn(async |state, req, next| {
-                        AuthGuard::new().validate_request(state, req, next).await
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::UsersWrite)
+                            .validate_request(state, req, next)
+                            .await
                     })),
             )
             .route(
                 "/role/{id}",
                 put(Self::update_user_role)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        RolesGuard::new(vec![UserRole::Admin])
+                        PermissionGuard::new(Permission::UsersRoleUpdate)
+                            .validate_request(user, req, next)
+                            .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::UsersWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/me/api-keys",
+                post(Self::create_api_key)
+                    .get(Self::get_api_keys)
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new().validate_request(state, req, next).await
+                    })),
+            )
+            .route(
+                "/me/api-keys/{id}",
+                delete(Self::delete_api_key).layer(middleware::from_fn(
+                    async |state, req, next| {
+                        AuthGuard::new().validate_request(state, req, next).await
+                    },
+                )),
+            )
+            .route(
+                "/mfa-policy",
+                put(Self::update_mfa_policy)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        PermissionGuard::new(Permission::MfaPolicyUpdate)
                             .validate_request(user, req, next)
                             .await
                     }))
@@ -74,7 +117,23 @@ impl UsersHandler {
                 "/{id}",
                 delete(Self::delete_user)
                     .layer(middleware::from_fn(async |user, req, next| {
-                        SelfGuard::new(vec![UserRole::Admin])
+                        SelfGuard::new(Permission::UsersDeleteAny)
+                            .validate_request(user, req, next)
+                            .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::UsersWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
+            .route(
+                "/{id}/suspension",
+                put(Self::suspend_user)
+                    .delete(Self::unsuspend_user)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        PermissionGuard::new(Permission::UsersSuspend)
                             .validate_request(user, req, next)
                             .await
                     }))
@@ -82,36 +141,60 @@ impl UsersHandler {
                         AuthGuard::new().validate_request(state, req, next).await
                     })),
             )
+            .route(
+                "/{id}/sessions",
+                delete(Self::revoke_user_sessions)
+                    .layer(middleware::from_fn(async |user, req, next| {
+                        PermissionGuard::new(Permission::UsersSessionsRevoke)
+                            .validate_request(user, req, next)
+                            .await
+                    }))
+                    .layer(middleware::from_fn(async |state, req, next| {
+                        AuthGuard::new()
+                            .allow_api_key(ApiKeyScope::UsersWrite)
+                            .validate_request(state, req, next)
+                            .await
+                    })),
+            )
             .layer(Extension(self.app_state.clone()))
             .layer(Extension(users_service))
     }
     async fn get_user(
         Extension(users_service): Extension<UsersService>,
         Path(id): Path<String>,
+        headers: HeaderMap,
     ) -> Result<Response, HttpError> {
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
-        users_service.get_user(uuid).await
+        users_service.get_user(uuid, &headers).await
     }
 
     async fn get_users(
-        Query(query_params): Query<QueryRangeDto>,
+        Query(query_params): Query<UsersQueryDto>,
         Extension(users_service): Extension<UsersService>,
     ) -> Result<Response, HttpError> {
         query_params
             .validate()
             .map_err(|e| HttpError::bad_request(e.to_string()))?;
 
-        let page = query_params.page.unwrap_or(1);
-        let limit = query_params.limit.unwrap_or(10);
+        let params = ListParams::new(
+            query_params.page,
+            query_params.limit,
+            query_params.cursor.as_deref(),
+            query_params.sort.as_deref(),
+            USER_SORT_FIELDS,
+        )?;
 
-        users_service.get_users(page, limit).await
+        users_service
+            .get_users(UserFilter::from_query(&query_params), params)
+            .await
     }
 
     async fn update_user(
         Extension(users_service): Extension<UsersService>,
         Path(id): Path<String>,
+        headers: HeaderMap,
         Json(body): Json<UpdateUserDto>,
     ) -> Result<Response, HttpError> {
         body.validate()
@@ -120,7 +203,7 @@ impl UsersHandler {
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
-        users_service.update_user(uuid, body).await
+        users_service.update_user(uuid, body, &headers).await
     }
 
     async fn update_user_role(
@@ -128,6 +211,9 @@ impl UsersHandler {
         Path(id): Path<String>,
         Json(body): Json<UpdateUserRoleDto>,
     ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
@@ -136,11 +222,86 @@ impl UsersHandler {
 
     async fn delete_user(
         Extension(users_service): Extension<UsersService>,
+        Extension(user): Extension<User>,
+        Path(id): Path<String>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        users_service.delete_user(user.id, uuid).await
+    }
+
+    async fn suspend_user(
+        Extension(users_service): Extension<UsersService>,
+        Extension(user): Extension<User>,
+        Path(id): Path<String>,
+        Json(body): Json<SuspendUserDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        users_service.suspend_user(user.id, uuid, body).await
+    }
+
+    async fn unsuspend_user(
+        Extension(users_service): Extension<UsersService>,
+        Path(id): Path<String>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        users_service.unsuspend_user(uuid).await
+    }
+
+    async fn revoke_user_sessions(
+        Extension(users_service): Extension<UsersService>,
+        Path(id): Path<String>,
+    ) -> Result<Response, HttpError> {
+        let uuid = Uuid::parse_str(&id)
+            .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
+
+        users_service.revoke_user_sessions(uuid).await
+    }
+
+    async fn update_mfa_policy(
+        Extension(users_service): Extension<UsersService>,
+        Json(body): Json<UpdateMfaPolicyDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        users_service.update_mfa_policy(body).await
+    }
+
+    async fn create_api_key(
+        Extension(users_service): Extension<UsersService>,
+        Extension(user): Extension<User>,
+        Json(body): Json<CreateApiKeyDto>,
+    ) -> Result<Response, HttpError> {
+        body.validate()
+            .map_err(|e| HttpError::bad_request(e.to_string()))?;
+
+        users_service.create_api_key(user.id, body).await
+    }
+
+    async fn get_api_keys(
+        Extension(users_service): Extension<UsersService>,
+        Extension(user): Extension<User>,
+    ) -> Result<Response, HttpError> {
+        users_service.get_api_keys(user.id).await
+    }
+
+    async fn delete_api_key(
+        Extension(users_service): Extension<UsersService>,
+        Extension(user): Extension<User>,
         Path(id): Path<String>,
     ) -> Result<Response, HttpError> {
         let uuid = Uuid::parse_str(&id)
             .map_err(|_| HttpError::bad_request("Invalid UUID format for `id` param"))?;
 
-        users_service.delete_user(uuid).await
+        users_service.delete_api_key(user.id, uuid).await
     }
 }
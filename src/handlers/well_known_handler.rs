@@ -0,0 +1,34 @@
+use axum::{
+    Extension, Json, Router,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::AppState;
+
+#[derive(Debug, Clone)]
+pub struct WellKnownHandler {
+    app_state: AppState,
+}
+
+impl WellKnownHandler {
+    pub fn new(app_state: AppState) -> Self {
+        Self { app_state }
+    }
+
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/jwks.json", get(Self::jwks))
+            .layer(Extension(self.app_state.clone()))
+    }
+
+    async fn jwks(Extension(app_state): Extension<AppState>) -> Response {
+        (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "public, max-age=300")],
+            Json(app_state.access_keys.jwks().clone()),
+        )
+            .into_response()
+    }
+}
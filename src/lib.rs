@@ -7,10 +7,21 @@ pub mod router;
 pub mod services;
 pub mod utils;
 
-use crate::{db::DBClient, utils::config::Env};
+use std::sync::Arc;
+
+use crate::{
+    db::DBClient,
+    middlewares::rate_limit_guard::RateLimiter,
+    utils::{config::Env, jwt_keys::JwtKeys, login_throttle::LoginAttemptStore, mailer::Mailer},
+};
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub env: Env,
+    pub access_keys: JwtKeys,
+    pub refresh_keys: JwtKeys,
     pub db_client: DBClient,
+    pub mailer: Arc<dyn Mailer>,
+    pub login_attempts: Arc<dyn LoginAttemptStore>,
+    pub rate_limiter: RateLimiter,
 }
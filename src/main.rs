@@ -1,9 +1,9 @@
-use std::error::Error;
+use std::{error::Error, net::SocketAddr};
 
 use axum::{
     http::{
         Method,
-        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH},
     },
     serve,
 };
@@ -14,8 +14,13 @@ use tower_http::cors::{Any, CorsLayer};
 use axum_posts::{
     AppState,
     db::DBClient,
+    middlewares::rate_limit_guard::RateLimiter,
     router::ApiRouter,
-    utils::{config::Env, print_running},
+    utils::{
+        config::Env, jwt_keys::JwtKeys, login_throttle::login_attempt_store_from_env,
+        mailer::mailer_from_env, print_running, publish::spawn_publish_task,
+        purge::spawn_purge_task,
+    },
 };
 
 #[async_main]
@@ -26,14 +31,36 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     let env = Env::init();
     let db_client = DBClient::new(&env.database_url).await?;
+    let mailer = mailer_from_env(&env);
+    let login_attempts = login_attempt_store_from_env(&env, &db_client);
+    let access_keys = JwtKeys::access_from_env(&env);
+    let refresh_keys = JwtKeys::refresh_from_env(&env);
+
+    spawn_purge_task(db_client.clone(), &env);
+    spawn_publish_task(db_client.clone(), &env);
 
-    let app_state = AppState { env, db_client };
+    let app_state = AppState {
+        env,
+        access_keys,
+        refresh_keys,
+        db_client,
+        mailer,
+        login_attempts,
+        rate_limiter: RateLimiter::new(),
+    };
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
         //.allow_credentials(true);
-        .allow_methods([Method::GET, Method::POST, Method::PUT])
-        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE, IF_MATCH, IF_NONE_MATCH])
+        .expose_headers([ETAG]);
 
     let listener =
         TcpListener::bind(format!("{}:{}", app_state.env.ip, app_state.env.port)).await?;
@@ -41,7 +68,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
     print_running(&app_state.env.local, app_state.env.port, &app_state.env.ip);
 
-    serve(listener, app.router).await.map_err(|e| {
+    serve(
+        listener,
+        app.router
+            .into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(|e| {
         eprintln!("Failed to start the server: {e}");
         e
     })?;
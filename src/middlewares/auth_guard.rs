@@ -1,33 +1,72 @@
 use async_trait::async_trait;
-use axum::{Extension, extract::Request, http::header, middleware::Next, response::Response};
+use axum::{
+    Extension,
+    extract::Request,
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::errors::ErrorKind as JwtErrorKind;
 use uuid::Uuid;
 
 use crate::{
-    AppState, db::users_db::UserExt, error::HttpError, middlewares::Middleware,
-    utils::token::TokenClaims,
+    AppState,
+    db::{api_keys_db::ApiKeyExt, mfa_db::MfaExt, users_db::UserExt},
+    dtos::{
+        api_key_dto::ApiKeyScope,
+        user_dto::{User, UserStatus},
+    },
+    error::HttpError,
+    middlewares::Middleware,
+    utils::token::{TokenClaims, TokenType, api_key_from_headers, hash_token},
 };
 
-#[derive(Debug, Clone)]
-pub struct AuthGuard {}
+#[derive(Debug, Clone, Default)]
+pub struct AuthGuard {
+    allow_mfa_setup: bool,
+    api_key_scope: Option<ApiKeyScope>,
+    optional: bool,
+}
 
 impl AuthGuard {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            allow_mfa_setup: false,
+            api_key_scope: None,
+            optional: false,
+        }
     }
-}
 
-#[async_trait]
-impl Middleware for AuthGuard {
-    type Extractor = AppState;
+    pub fn allow_mfa_setup(mut self) -> Self {
+        self.allow_mfa_setup = true;
+        self
+    }
 
-    async fn validate_request(
+    pub fn allow_api_key(mut self, scope: ApiKeyScope) -> Self {
+        self.api_key_scope = Some(scope);
+        self
+    }
+
+    // requests without valid credentials pass through without a user
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    // public routes serve an invalid or stale token, or an api key they do not
+    // accept, like no credentials at all
+    fn serves_as_anonymous(&self, error: &HttpError, api_key: bool) -> bool {
+        self.optional
+            && (error.status == StatusCode::UNAUTHORIZED
+                || api_key && error.status == StatusCode::FORBIDDEN)
+    }
+
+    async fn authenticate_token(
         &self,
-        Extension(app_state): Extension<AppState>,
-        mut req: Request,
-        next: Next,
-    ) -> Result<Response, HttpError> {
-        let authorization = req
-            .headers()
+        app_state: &AppState,
+        headers: &HeaderMap,
+    ) -> Result<User, HttpError> {
+        let authorization = headers
             .get(header::AUTHORIZATION)
             .ok_or_else(|| HttpError::unauthorized("missing authorization header"))?;
 
@@ -42,10 +81,15 @@ impl Middleware for AuthGuard {
             }
         };
 
-        let claim = TokenClaims::decode(token, app_state.env.jwt_access_token_secert.as_bytes())
-            .map_err(|_| HttpError::unauthorized("invalid authorization token"))?
-            .validate()
-            .map_err(|_| HttpError::unauthorized("authorization token is expired or invalid"))?;
+        let claim =
+            TokenClaims::decode(token, TokenType::Access, &app_state.access_keys).map_err(|e| {
+                match e.kind() {
+                    JwtErrorKind::ExpiredSignature => {
+                        HttpError::unauthorized("authorization token is expired")
+                    }
+                    _ => HttpError::unauthorized("invalid authorization token"),
+                }
+            })?;
 
         let user_id = Uuid::parse_str(claim.sub.to_string().as_str())
             .map_err(|_| HttpError::unauthorized("invalid user ID in token"))?;
@@ -59,8 +103,175 @@ impl Middleware for AuthGuard {
                 HttpError::unauthorized("user not found or does not have access to this resource")
             })?;
 
+        if claim.ver != user.token_version {
+            return Err(HttpError::unauthorized(
+                "authorization token has been revoked",
+            ));
+        }
+
+        Ok(user)
+    }
+
+    async fn authenticate_api_key(
+        &self,
+        app_state: &AppState,
+        api_key: &str,
+    ) -> Result<User, HttpError> {
+        let Some(scope) = self.api_key_scope else {
+            return Err(HttpError::forbidden(
+                "api keys are not allowed on this route",
+            ));
+        };
+
+        let api_key = app_state
+            .db_client
+            .use_api_key(&hash_token(api_key))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check api key"))?
+            .ok_or_else(|| HttpError::unauthorized("invalid or expired api key"))?;
+
+        if !api_key.has_scope(scope) {
+            return Err(HttpError::forbidden(format!(
+                "api key is missing the `{scope}` scope"
+            )));
+        }
+
+        app_state
+            .db_client
+            .get_user(Some(api_key.user_id), None, None)
+            .await
+            .map_err(|_| HttpError::server_error("invaild checking for the user"))?
+            .ok_or_else(|| {
+                HttpError::unauthorized("user not found or does not have access to this resource")
+            })
+    }
+}
+
+#[async_trait]
+impl Middleware for AuthGuard {
+    type Extractor = AppState;
+
+    async fn validate_request(
+        &self,
+        Extension(app_state): Extension<AppState>,
+        mut req: Request,
+        next: Next,
+    ) -> Result<Response, HttpError> {
+        let api_key = api_key_from_headers(req.headers());
+
+        if self.optional && api_key.is_none() && !req.headers().contains_key(header::AUTHORIZATION)
+        {
+            return Ok(next.run(req).await);
+        }
+
+        let user = match &api_key {
+            Some(api_key) => self.authenticate_api_key(&app_state, api_key).await,
+            None => self.authenticate_token(&app_state, req.headers()).await,
+        };
+
+        let user = match user {
+            Ok(user) => user,
+            Err(e) if self.serves_as_anonymous(&e, api_key.is_some()) => {
+                return Ok(next.run(req).await);
+            }
+            Err(e) => return Err(e),
+        };
+
+        check_account_status(&user)?;
+
+        if !self.allow_mfa_setup && user.totp_enabled_at.is_none() {
+            let mfa_required = app_state
+                .db_client
+                .is_mfa_required(&user.role)
+                .await
+                .map_err(|_| HttpError::server_error("failed to check mfa policy"))?;
+
+            if mfa_required {
+                return Err(HttpError::forbidden(
+                    "two-factor authentication is required for your role",
+                ));
+            }
+        }
+
         req.extensions_mut().insert(user);
 
         Ok(next.run(req).await)
     }
 }
+
+pub fn check_account_status(user: &User) -> Result<(), HttpError> {
+    if user.status == UserStatus::Deactivated {
+        return Err(HttpError::unauthorized("account has been deleted"));
+    }
+
+    if user.is_suspended() {
+        let message = match user.suspended_until {
+            Some(suspended_until) => format!(
+                "account is suspended until {}",
+                suspended_until.to_rfc3339()
+            ),
+            None => "account is suspended".to_string(),
+        };
+
+        return Err(HttpError::forbidden(match &user.suspension_reason {
+            Some(reason) => format!("{message}: {reason}"),
+            None => message,
+        }));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_accepted() -> HttpError {
+        HttpError::forbidden("api keys are not allowed on this route")
+    }
+
+    #[test]
+    fn optional_routes_ignore_api_keys_they_do_not_accept() {
+        let guard = AuthGuard::new().optional();
+
+        assert!(guard.serves_as_anonymous(&not_accepted(), true));
+        assert!(guard.serves_as_anonymous(
+            &HttpError::forbidden("api key is missing the `posts:write` scope"),
+            true
+        ));
+        assert!(
+            guard.serves_as_anonymous(&HttpError::unauthorized("invalid or expired api key"), true)
+        );
+    }
+
+    #[test]
+    fn optional_routes_ignore_invalid_tokens() {
+        let guard = AuthGuard::new().optional();
+
+        assert!(guard.serves_as_anonymous(
+            &HttpError::unauthorized("invalid authorization token"),
+            false
+        ));
+    }
+
+    #[test]
+    fn optional_routes_keep_other_failures() {
+        let guard = AuthGuard::new().optional();
+
+        assert!(!guard.serves_as_anonymous(&HttpError::forbidden("forbidden"), false));
+        assert!(
+            !guard.serves_as_anonymous(&HttpError::server_error("failed to check api key"), true)
+        );
+    }
+
+    #[test]
+    fn required_routes_reject_credentials_they_do_not_accept() {
+        let guard = AuthGuard::new();
+
+        assert!(!guard.serves_as_anonymous(&not_accepted(), true));
+        assert!(!guard.serves_as_anonymous(
+            &HttpError::unauthorized("invalid authorization token"),
+            false
+        ));
+    }
+}
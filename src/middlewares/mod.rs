@@ -1,6 +1,9 @@
 pub mod auth_guard;
-pub mod roles_guard;
+pub mod owner_guard;
+pub mod permission_guard;
+pub mod rate_limit_guard;
 pub mod self_guard;
+pub mod verified_guard;
 
 use async_trait::async_trait;
 use axum::{Extension, extract::Request, middleware::Next, response::IntoResponse};
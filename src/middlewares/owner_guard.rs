@@ -0,0 +1,128 @@
+use std::{collections::HashMap, marker::PhantomData};
+
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    extract::{FromRequestParts, Path, Request},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::Error as SqlxError;
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    db::DBClient,
+    dtos::{
+        comment_dto::{Comment, CommentOwnership},
+        post_dto::Post,
+        role_dto::Permission,
+        user_dto::User,
+    },
+    error::HttpError,
+    middlewares::{Middleware, permission_guard::has_permission},
+};
+
+pub trait Owned {
+    fn is_owned_by(&self, user_id: Uuid) -> bool;
+}
+
+impl Owned for Post {
+    fn is_owned_by(&self, user_id: Uuid) -> bool {
+        self.user_id == user_id
+    }
+}
+
+impl Owned for Comment {
+    fn is_owned_by(&self, user_id: Uuid) -> bool {
+        self.user_id == user_id
+    }
+}
+
+impl Owned for CommentOwnership {
+    fn is_owned_by(&self, user_id: Uuid) -> bool {
+        self.user_id == user_id || self.post_owner_id == user_id
+    }
+}
+
+pub struct OwnerGuard<F, T> {
+    loader: F,
+    permission: Permission,
+    param: &'static str,
+    resource: PhantomData<fn() -> T>,
+}
+
+impl<F, Fut, T> OwnerGuard<F, T>
+where
+    F: Fn(DBClient, Uuid) -> Fut,
+    Fut: Future<Output = Result<Option<T>, SqlxError>>,
+{
+    pub fn new(loader: F, permission: Permission) -> Self {
+        Self {
+            loader,
+            permission,
+            param: "id",
+            resource: PhantomData,
+        }
+    }
+
+    // the path param holding the resource id, `id` by default
+    pub fn with_param(mut self, param: &'static str) -> Self {
+        self.param = param;
+        self
+    }
+}
+
+#[async_trait]
+impl<F, Fut, T> Middleware for OwnerGuard<F, T>
+where
+    F: Fn(DBClient, Uuid) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<T>, SqlxError>> + Send,
+    T: Owned + Send,
+{
+    type Extractor = User;
+
+    async fn validate_request(
+        &self,
+        user: Extension<User>,
+        mut req: Request,
+        next: Next,
+    ) -> Result<Response, HttpError> {
+        let (mut parts, body) = req.into_parts();
+
+        let Path(params): Path<HashMap<String, String>> = Path::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|_| {
+                HttpError::bad_request(format!("Missing or invalid `{}` param", self.param))
+            })?;
+
+        let id = params.get(self.param).ok_or_else(|| {
+            HttpError::bad_request(format!("Missing or invalid `{}` param", self.param))
+        })?;
+
+        let uuid = Uuid::parse_str(id).map_err(|_| {
+            HttpError::bad_request(format!("Invalid UUID format for `{}` param", self.param))
+        })?;
+
+        req = Request::from_parts(parts, body);
+
+        let db_client = req
+            .extensions()
+            .get::<AppState>()
+            .map(|app_state| app_state.db_client.clone())
+            .ok_or_else(|| HttpError::server_error("app state is not available"))?;
+
+        let resource = (self.loader)(db_client.clone(), uuid)
+            .await
+            .map_err(|_| HttpError::server_error("failed to load the resource"))?
+            .ok_or_else(|| HttpError::not_found(format!("resource with id: {uuid} not found")))?;
+
+        if resource.is_owned_by(user.id)
+            || has_permission(&db_client, &user, self.permission).await?
+        {
+            return Ok(next.run(req).await);
+        }
+
+        Err(HttpError::forbidden("user does not own this resource"))
+    }
+}
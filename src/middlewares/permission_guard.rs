@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use axum::{Extension, extract::Request, middleware::Next, response::Response};
+
+use crate::{
+    AppState,
+    db::{DBClient, roles_db::RoleExt},
+    dtos::{role_dto::Permission, user_dto::User},
+    error::HttpError,
+    middlewares::Middleware,
+};
+
+#[derive(Debug, Clone)]
+pub struct PermissionGuard {
+    permission: Permission,
+}
+
+impl PermissionGuard {
+    pub fn new(permission: Permission) -> Self {
+        Self { permission }
+    }
+}
+
+#[async_trait]
+impl Middleware for PermissionGuard {
+    type Extractor = User;
+
+    async fn validate_request(
+        &self,
+        Extension(user): Extension<User>,
+        req: Request,
+        next: Next,
+    ) -> Result<Response, HttpError> {
+        let db_client = req
+            .extensions()
+            .get::<AppState>()
+            .map(|app_state| app_state.db_client.clone())
+            .ok_or_else(|| HttpError::server_error("app state is not available"))?;
+
+        if !has_permission(&db_client, &user, self.permission).await? {
+            return Err(HttpError::forbidden(
+                "user does not have the required permission",
+            ));
+        }
+
+        Ok(next.run(req).await)
+    }
+}
+
+pub async fn has_permission(
+    db_client: &DBClient,
+    user: &User,
+    permission: Permission,
+) -> Result<bool, HttpError> {
+    db_client
+        .role_has_permission(&user.role, &permission.to_string())
+        .await
+        .map_err(|_| HttpError::server_error("failed to check user permissions"))
+}
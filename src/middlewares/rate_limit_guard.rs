@@ -0,0 +1,222 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use axum::{
+    Extension,
+    extract::Request,
+    http::{Extensions, HeaderMap, HeaderName, HeaderValue, Method, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    db::api_keys_db::ApiKeyExt,
+    dtos::user_dto::User,
+    error::HttpError,
+    middlewares::Middleware,
+    utils::{
+        client_info::ClientInfo,
+        config::RateLimitPolicy,
+        token::{TokenClaims, TokenType, api_key_from_headers, hash_token},
+    },
+};
+
+const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn new(policy: RateLimitPolicy, now: Instant) -> Self {
+        let capacity = f64::from(policy.capacity.max(1));
+
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_sec: capacity / policy.period_sec.max(1) as f64,
+            updated_at: now,
+        }
+    }
+
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        (self.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RateLimitDecision {
+    allowed: bool,
+    limit: u32,
+    remaining: u32,
+    reset_sec: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn acquire(&self, key: String, policy: RateLimitPolicy) -> Option<RateLimitDecision> {
+        let mut buckets = self.buckets.lock().ok()?;
+
+        let now = Instant::now();
+
+        if buckets.len() > MAX_TRACKED_BUCKETS {
+            buckets.retain(|_, bucket| bucket.tokens_at(now) < bucket.capacity);
+        }
+
+        let bucket = buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(policy, now));
+
+        bucket.tokens = bucket.tokens_at(now);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        let reset_sec = if allowed {
+            ((bucket.capacity - bucket.tokens) / bucket.refill_per_sec).ceil()
+        } else {
+            ((1.0 - bucket.tokens) / bucket.refill_per_sec).ceil()
+        };
+
+        Some(RateLimitDecision {
+            allowed,
+            limit: bucket.capacity as u32,
+            remaining: bucket.tokens.floor() as u32,
+            reset_sec: reset_sec.max(0.0) as u64,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitGuard {
+    scope: &'static str,
+    policy: RateLimitPolicy,
+    read_policy: Option<RateLimitPolicy>,
+}
+
+impl RateLimitGuard {
+    pub fn new(scope: &'static str, policy: RateLimitPolicy) -> Self {
+        Self {
+            scope,
+            policy,
+            read_policy: None,
+        }
+    }
+
+    pub fn with_read_policy(mut self, policy: RateLimitPolicy) -> Self {
+        self.read_policy = Some(policy);
+        self
+    }
+
+    async fn client_key(
+        app_state: &AppState,
+        headers: &HeaderMap,
+        extensions: &Extensions,
+    ) -> String {
+        if let Some(user) = extensions.get::<User>() {
+            return format!("user:{}", user.id);
+        }
+
+        // made up keys would otherwise each get a fresh bucket
+        if let Some(api_key) = api_key_from_headers(headers) {
+            let api_key = app_state
+                .db_client
+                .get_api_key(&hash_token(&api_key))
+                .await
+                .ok()
+                .flatten();
+
+            if let Some(api_key) = api_key {
+                return format!("api_key:{}", api_key.id);
+            }
+        }
+
+        let user_id = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| {
+                TokenClaims::decode(token, TokenType::Access, &app_state.access_keys).ok()
+            })
+            .map(|claims| claims.sub);
+
+        if let Some(user_id) = user_id {
+            return format!("user:{user_id}");
+        }
+
+        let client = ClientInfo::new(headers, extensions);
+        format!("ip:{}", client.ip_address.unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Middleware for RateLimitGuard {
+    type Extractor = AppState;
+
+    async fn validate_request(
+        &self,
+        Extension(app_state): Extension<AppState>,
+        req: Request,
+        next: Next,
+    ) -> Result<Response, HttpError> {
+        let is_read = matches!(*req.method(), Method::GET | Method::HEAD);
+        let (policy, class) = match self.read_policy {
+            Some(read_policy) if is_read => (read_policy, "read"),
+            _ => (self.policy, "all"),
+        };
+
+        let key = format!(
+            "{}:{}:{}",
+            self.scope,
+            class,
+            Self::client_key(&app_state, req.headers(), req.extensions()).await
+        );
+
+        let decision = app_state
+            .rate_limiter
+            .acquire(key, policy)
+            .ok_or_else(|| HttpError::server_error("failed to check rate limit"))?;
+
+        if !decision.allowed {
+            return Err(HttpError::too_many_requests("rate limit exceeded")
+                .with_header(RATE_LIMIT_LIMIT, decision.limit)
+                .with_header(RATE_LIMIT_REMAINING, decision.remaining)
+                .with_header(RATE_LIMIT_RESET, decision.reset_sec)
+                .with_header(header::RETRY_AFTER, decision.reset_sec));
+        }
+
+        let mut response = next.run(req).await;
+
+        let headers = response.headers_mut();
+        headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(decision.limit));
+        headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(decision.remaining));
+        headers.insert(RATE_LIMIT_RESET, HeaderValue::from(decision.reset_sec));
+
+        Ok(response)
+    }
+}
@@ -8,18 +8,19 @@ use axum::{
 use uuid::Uuid;
 
 use crate::{
-    dtos::user_dto::{User, UserRole},
+    AppState,
+    dtos::{role_dto::Permission, user_dto::User},
     error::HttpError,
-    middlewares::Middleware,
+    middlewares::{Middleware, permission_guard::has_permission},
 };
 
 pub struct SelfGuard {
-    roles: Vec<UserRole>,
+    permission: Permission,
 }
 
 impl SelfGuard {
-    pub fn new(roles: Vec<UserRole>) -> Self {
-        Self { roles }
+    pub fn new(permission: Permission) -> Self {
+        Self { permission }
     }
 }
 
@@ -48,9 +49,15 @@ impl Middleware for SelfGuard {
             return Ok(next.run(req).await);
         }
 
-        if !self.roles.contains(&user.role) {
-            return Err(HttpError::unauthorized(
-                "User does not have the required role",
+        let db_client = req
+            .extensions()
+            .get::<AppState>()
+            .map(|app_state| app_state.db_client.clone())
+            .ok_or_else(|| HttpError::server_error("app state is not available"))?;
+
+        if !has_permission(&db_client, &user, self.permission).await? {
+            return Err(HttpError::forbidden(
+                "user does not have the required permission",
             ));
         }
 
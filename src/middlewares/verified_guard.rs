@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use axum::{Extension, extract::Request, middleware::Next, response::Response};
+
+use crate::{dtos::user_dto::User, error::HttpError, middlewares::Middleware};
+
+#[derive(Debug, Clone, Default)]
+pub struct VerifiedGuard {}
+
+impl VerifiedGuard {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait]
+impl Middleware for VerifiedGuard {
+    type Extractor = User;
+
+    async fn validate_request(
+        &self,
+        Extension(user): Extension<User>,
+        req: Request,
+        next: Next,
+    ) -> Result<Response, HttpError> {
+        if user.email_verified_at.is_none() {
+            return Err(HttpError::forbidden("email address is not verified"));
+        }
+
+        Ok(next.run(req).await)
+    }
+}
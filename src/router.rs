@@ -1,11 +1,12 @@
-use axum::Router;
+use axum::{Extension, Router, middleware};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 use crate::{
     AppState,
     handlers::Handlers,
+    middlewares::{Middleware, rate_limit_guard::RateLimitGuard},
     services::Services,
-    utils::config::Routes::{Auth, Base, Posts, Users},
+    utils::config::Routes::{Auth, Base, Comments, Posts, Roles, Tags, Users, WellKnown},
 };
 
 pub struct ApiRouter {
@@ -15,7 +16,8 @@ pub struct ApiRouter {
 impl ApiRouter {
     pub fn new(app_state: AppState) -> Self {
         let handlers = Handlers::new(app_state.clone());
-        let services = Services::new(app_state);
+        let services = Services::new(app_state.clone());
+        let env = &app_state.env;
         let router = Router::new()
             .nest(
                 &Base.to_string(),
@@ -23,17 +25,52 @@ impl ApiRouter {
                     .merge(handlers.root_handler.router())
                     .nest(
                         &Auth.to_string(),
-                        handlers.auth_handler.router(services.auth_service),
+                        Self::rate_limited(
+                            handlers.auth_handler.router(services.auth_service),
+                            RateLimitGuard::new("auth", env.rate_limit_auth),
+                        ),
                     )
                     .nest(
                         &Users.to_string(),
-                        handlers.users_handler.router(services.users_service),
+                        Self::rate_limited(
+                            handlers.users_handler.router(services.users_service),
+                            RateLimitGuard::new("users", env.rate_limit_users),
+                        ),
                     )
                     .nest(
                         &Posts.to_string(),
-                        handlers.posts_handler.router(services.posts_service),
+                        Self::rate_limited(
+                            handlers.posts_handler.router(services.posts_service),
+                            RateLimitGuard::new("posts", env.rate_limit_posts)
+                                .with_read_policy(env.rate_limit_posts_read),
+                        ),
+                    )
+                    .nest(
+                        &Comments.to_string(),
+                        Self::rate_limited(
+                            handlers.comments_handler.router(services.comments_service),
+                            RateLimitGuard::new("comments", env.rate_limit_posts)
+                                .with_read_policy(env.rate_limit_posts_read),
+                        ),
+                    )
+                    .nest(
+                        &Tags.to_string(),
+                        Self::rate_limited(
+                            handlers.tags_handler.router(services.tags_service),
+                            RateLimitGuard::new("tags", env.rate_limit_posts)
+                                .with_read_policy(env.rate_limit_posts_read),
+                        ),
+                    )
+                    .nest(
+                        &Roles.to_string(),
+                        Self::rate_limited(
+                            handlers.roles_handler.router(services.roles_service),
+                            RateLimitGuard::new("roles", env.rate_limit_users),
+                        ),
                     ),
             )
+            .nest(&WellKnown.to_string(), handlers.well_known_handler.router())
+            .layer(Extension(app_state.clone()))
             .layer(TraceLayer::new_for_http());
         Self { router }
     }
@@ -42,4 +79,11 @@ impl ApiRouter {
         let router = self.router.layer(cors);
         Self { router }
     }
+
+    fn rate_limited(router: Router, guard: RateLimitGuard) -> Router {
+        router.layer(middleware::from_fn(move |state, req, next| {
+            let guard = guard.clone();
+            async move { guard.validate_request(state, req, next).await }
+        }))
+    }
 }
@@ -1,60 +1,144 @@
+use std::sync::Arc;
+
 use time::Duration;
 
 use axum::{
     Json,
     http::{HeaderMap, HeaderValue, StatusCode, header},
-    response::{IntoResponse, Response},
+    response::{IntoResponse, Redirect, Response},
 };
-use axum_extra::extract::cookie::Cookie;
+use axum_extra::extract::cookie::{Cookie, SameSite};
+use chrono::{TimeDelta, Utc};
+use jsonwebtoken::errors::ErrorKind as JwtErrorKind;
 use serde_json::json;
+use tokio::spawn;
+use uuid::Uuid;
 
 use crate::{
     AppState,
-    db::{DBClient, users_db::UserExt},
+    db::{
+        DBClient, mfa_db::MfaExt, oauth_db::OAuthExt, sessions_db::SessionExt,
+        user_tokens_db::UserTokenExt, users_db::UserExt,
+    },
     dtos::{
-        auth_dto::{LoginDto, RegisterDto},
-        user_dto::UserResponseDto,
+        auth_dto::{
+            ForgotPasswordDto, LoginDto, LoginMfaDto, MfaCodeDto, RegisterDto,
+            ResendVerificationDto, ResetPasswordDto, VerifyEmailDto,
+        },
+        oauth_dto::{OAuthCallbackDto, OAuthProfile},
+        user_dto::{User, UserResponseDto, UserStatus},
+        user_token_dto::UserTokenKind,
     },
     error::HttpError,
-    utils::{config::Env, password::PasswordArgon, token::TokenClaims},
+    middlewares::auth_guard::check_account_status,
+    utils::{
+        client_info::ClientInfo,
+        config::{Env, OAuthProvider, Routes},
+        jwt_keys::JwtKeys,
+        login_throttle::LoginThrottle,
+        mailer::{Mail, Mailer},
+        oauth::OAuthClient,
+        password::PasswordArgon,
+        token::{TokenClaims, TokenType, generate_token, hash_token},
+        totp::Totp,
+    },
 };
 
+pub const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
 #[derive(Debug, Clone)]
 pub struct AuthService {
     db_client: DBClient,
     env: Env,
+    access_keys: JwtKeys,
+    refresh_keys: JwtKeys,
+    mailer: Arc<dyn Mailer>,
+    login_throttle: LoginThrottle,
+    oauth: OAuthClient,
 }
 
 impl AuthService {
     pub fn new(app_state: AppState) -> Self {
         Self {
+            login_throttle: LoginThrottle::new(app_state.login_attempts, &app_state.env),
+            oauth: OAuthClient::new(&app_state.env),
             db_client: app_state.db_client,
             env: app_state.env,
+            access_keys: app_state.access_keys,
+            refresh_keys: app_state.refresh_keys,
+            mailer: app_state.mailer,
         }
     }
 
-    pub fn refresh(&self, cookie: Cookie) -> Result<Response, HttpError> {
+    pub async fn refresh(
+        &self,
+        cookie: Cookie<'_>,
+        client: ClientInfo,
+    ) -> Result<Response, HttpError> {
         if cookie.name() != "refresh_token" {
             return Err(HttpError::bad_request("refresh token not found"));
         }
 
-        let refresh_token =
-            TokenClaims::decode(cookie.value(), self.env.jwt_refresh_token_secert.as_bytes())
-                .map_err(|_| HttpError::unauthorized("invalid refresh token"))?
-                .validate()
-                .map_err(|_| HttpError::unauthorized("expired refresh token"))?;
+        let claims = TokenClaims::decode(cookie.value(), TokenType::Refresh, &self.refresh_keys)
+            .map_err(|e| match e.kind() {
+                JwtErrorKind::ExpiredSignature => HttpError::unauthorized("expired refresh token"),
+                _ => HttpError::unauthorized("invalid refresh token"),
+            })?;
+
+        let stored_token = self
+            .db_client
+            .get_refresh_token(&hash_token(cookie.value()))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check refresh token"))?
+            .ok_or_else(|| HttpError::unauthorized("invalid refresh token"))?;
+
+        if stored_token.rotated_at.is_some() || stored_token.revoked_at.is_some() {
+            self.revoke_family(stored_token.family_id).await?;
+            return Err(HttpError::unauthorized("refresh token has been revoked"));
+        }
+
+        if stored_token.expires_at <= Utc::now() {
+            return Err(HttpError::unauthorized("expired refresh token"));
+        }
+
+        let rotated_token = self
+            .db_client
+            .rotate_refresh_token(stored_token.id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to rotate refresh token"))?;
+
+        let Some(rotated_token) = rotated_token else {
+            self.revoke_family(stored_token.family_id).await?;
+            return Err(HttpError::unauthorized("refresh token has been revoked"));
+        };
+
+        let user = self
+            .db_client
+            .get_user(Some(rotated_token.user_id), None, None)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check user"))?
+            .ok_or_else(|| HttpError::unauthorized("user no longer exists"))?;
+
+        if claims.ver != user.token_version {
+            self.revoke_family(rotated_token.family_id).await?;
+            return Err(HttpError::unauthorized("refresh token has been revoked"));
+        }
+
+        check_account_status(&user)?;
 
         let access_token = TokenClaims::encode(
-            &refresh_token.sub.to_string(),
-            self.env.jwt_access_token_secert.as_bytes(),
+            &user,
+            TokenType::Access,
+            &self.access_keys,
             self.env.jwt_access_token_expires,
         )
         .map_err(|_| HttpError::server_error("failed to generate access token"))?;
 
-        let mut headers = HeaderMap::new();
-        let auth_value = HeaderValue::from_str(&format!("Bearer {access_token}"))
-            .map_err(|_| HttpError::server_error("failed to set access token header"))?;
-        headers.insert(header::AUTHORIZATION, auth_value);
+        let cookie = self
+            .issue_refresh_token(&user, rotated_token.family_id, client)
+            .await?;
+
+        let headers = Self::token_headers(&access_token, &cookie)?;
 
         Ok((StatusCode::OK, headers).into_response())
     }
@@ -73,45 +157,734 @@ impl AuthService {
         let password = PasswordArgon::hash(&data.password)
             .map_err(|_| HttpError::server_error("failed to hash password"))?;
 
-        self.db_client
+        let user = self
+            .db_client
             .create_user(data.name, data.email, password)
             .await
             .map_err(|_| HttpError::server_error("failed to create user"))?;
 
+        if let Err(e) = self.send_verification_email(&user).await {
+            eprintln!("Failed to send verification email: {e}");
+        }
+
         Ok(StatusCode::CREATED)
     }
 
-    pub async fn login(&self, data: LoginDto) -> Result<Response, HttpError> {
+    pub async fn verify_email(&self, data: VerifyEmailDto) -> Result<Response, HttpError> {
+        let token = self
+            .db_client
+            .consume_user_token(UserTokenKind::EmailVerification, &hash_token(&data.token))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check verification token"))?
+            .ok_or_else(|| HttpError::bad_request("invalid or expired verification token"))?;
+
+        self.db_client
+            .verify_user_email(token.user_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to verify email"))?
+            .ok_or_else(|| HttpError::not_found("user no longer exists"))?;
+
+        let body = Json(json!({
+            "message": "Email verified",
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn resend_verification(
+        &self,
+        data: ResendVerificationDto,
+    ) -> Result<Response, HttpError> {
         let user = self
             .db_client
             .get_user(None, None, Some(&data.email))
             .await
-            .map_err(|_| HttpError::unauthorized("invalid credentials"))?
-            .ok_or_else(|| HttpError::unauthorized("invalid credentials"))?;
+            .map_err(|_| HttpError::server_error("failed to check existing user"))?;
+
+        if let Some(user) = user.filter(|user| user.email_verified_at.is_none())
+            && let Err(e) = self.send_verification_email(&user).await
+        {
+            eprintln!("Failed to send verification email: {e}");
+        }
+
+        let body = Json(json!({
+            "message": "If the account exists and is unverified, a verification email has been sent",
+        }));
+
+        Ok((StatusCode::ACCEPTED, body).into_response())
+    }
+
+    pub async fn login(&self, data: LoginDto, client: ClientInfo) -> Result<Response, HttpError> {
+        let email_key = LoginThrottle::email_key(&data.email);
+        let ip_key = client.ip_address.as_deref().map(LoginThrottle::ip_key);
+
+        self.login_throttle
+            .check(&email_key, ip_key.as_deref())
+            .await?;
+
+        let user = self
+            .db_client
+            .get_user(None, None, Some(&data.email))
+            .await
+            .map_err(|_| HttpError::unauthorized("invalid credentials"))?;
+
+        let Some(user) = user else {
+            self.login_throttle
+                .record_failure(&email_key, ip_key.as_deref())
+                .await?;
+            return Err(HttpError::unauthorized("invalid credentials"));
+        };
 
         let password_matches = PasswordArgon::compare(&data.password, &user.password)
             .map_err(|_| HttpError::server_error("password comparison failed"))?;
 
         if !password_matches {
+            self.login_throttle
+                .record_failure(&email_key, ip_key.as_deref())
+                .await?;
             return Err(HttpError::unauthorized("invalid credentials"));
         }
 
-        let user_response = UserResponseDto::from_user(&user);
+        self.login_throttle.clear(&email_key).await?;
+
+        self.complete_login(&user, client).await
+    }
+
+    pub async fn oauth_start(
+        &self,
+        provider: &str,
+        user: Option<User>,
+    ) -> Result<Response, HttpError> {
+        let provider = self
+            .oauth
+            .provider(provider)
+            .ok_or_else(|| HttpError::not_found("unknown oauth provider"))?;
+
+        let state = generate_token();
+        let state_hash = hash_token(&state);
+        let code_verifier = generate_token();
+        let nonce = generate_token();
+        let expires_at = Utc::now() + TimeDelta::seconds(self.env.oauth_state_expires);
+
+        let authorize_url = self
+            .oauth
+            .authorize_url(provider, &state, &code_verifier, &nonce)
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        self.db_client
+            .create_oauth_state(
+                state_hash.clone(),
+                &provider.name,
+                code_verifier,
+                nonce,
+                user.map(|user| user.id),
+                expires_at,
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to store oauth state"))?;
+
+        // binds the state to the browser that started the login
+        let cookie =
+            Self::oauth_state_cookie(state_hash, Duration::seconds(self.env.oauth_state_expires));
+
+        let mut headers = HeaderMap::new();
+        let cookie_value = HeaderValue::from_str(&cookie.to_string())
+            .map_err(|_| HttpError::server_error("failed to set oauth state cookie"))?;
+        headers.append(header::SET_COOKIE, cookie_value);
+
+        Ok((headers, Redirect::to(&authorize_url)).into_response())
+    }
+
+    pub async fn oauth_callback(
+        &self,
+        provider: &str,
+        query: OAuthCallbackDto,
+        state_cookie: Option<String>,
+        client: ClientInfo,
+    ) -> Result<Response, HttpError> {
+        let mut response = self
+            .complete_oauth_callback(provider, query, state_cookie, client)
+            .await
+            .unwrap_or_else(|e| e.into_response());
+
+        let cookie = Self::oauth_state_cookie(String::new(), Duration::ZERO);
+
+        let cookie_value = HeaderValue::from_str(&cookie.to_string())
+            .map_err(|_| HttpError::server_error("failed to clear oauth state cookie"))?;
+        response
+            .headers_mut()
+            .append(header::SET_COOKIE, cookie_value);
+
+        Ok(response)
+    }
+
+    async fn complete_oauth_callback(
+        &self,
+        provider: &str,
+        query: OAuthCallbackDto,
+        state_cookie: Option<String>,
+        client: ClientInfo,
+    ) -> Result<Response, HttpError> {
+        let provider = self
+            .oauth
+            .provider(provider)
+            .ok_or_else(|| HttpError::not_found("unknown oauth provider"))?;
+
+        if let Some(error) = query.error {
+            return Err(HttpError::bad_request(format!(
+                "oauth login failed: {}",
+                query.error_description.unwrap_or(error)
+            )));
+        }
+
+        let (Some(code), Some(state)) = (query.code, query.state) else {
+            return Err(HttpError::bad_request("missing oauth code or state"));
+        };
+
+        let state_hash = hash_token(&state);
+
+        if state_cookie.as_deref() != Some(state_hash.as_str()) {
+            return Err(HttpError::bad_request(
+                "oauth state does not match this browser",
+            ));
+        }
+
+        let oauth_state = self
+            .db_client
+            .consume_oauth_state(&state_hash, &provider.name)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check oauth state"))?
+            .ok_or_else(|| HttpError::bad_request("invalid or expired oauth state"))?;
+
+        let tokens = self
+            .oauth
+            .exchange_code(provider, &code, &oauth_state.code_verifier)
+            .await
+            .map_err(|e| HttpError::new(StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+        let profile = self
+            .oauth
+            .fetch_profile(provider, &tokens, &oauth_state.nonce)
+            .await
+            .map_err(|e| HttpError::new(StatusCode::BAD_GATEWAY, e.to_string()))?;
+
+        let user = self
+            .find_or_create_oauth_user(provider, profile, oauth_state.user_id)
+            .await?;
+
+        self.complete_login(&user, client).await
+    }
+
+    pub async fn login_mfa(
+        &self,
+        data: LoginMfaDto,
+        client: ClientInfo,
+    ) -> Result<Response, HttpError> {
+        let mfa_token_hash = hash_token(&data.mfa_token);
+
+        let mfa_token = self
+            .db_client
+            .get_user_token(UserTokenKind::MfaPending, &mfa_token_hash)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check mfa token"))?
+            .ok_or_else(|| HttpError::unauthorized("invalid or expired mfa token"))?;
+
+        let user = self
+            .db_client
+            .get_user(Some(mfa_token.user_id), None, None)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check user"))?
+            .ok_or_else(|| HttpError::unauthorized("invalid or expired mfa token"))?;
+
+        let email_key = LoginThrottle::email_key(&user.email);
+        let ip_key = client.ip_address.as_deref().map(LoginThrottle::ip_key);
+
+        self.login_throttle
+            .check(&email_key, ip_key.as_deref())
+            .await?;
+
+        if !self.check_mfa_code(&user, &data.code).await? {
+            self.login_throttle
+                .record_failure(&email_key, ip_key.as_deref())
+                .await?;
+            return Err(HttpError::unauthorized("invalid two-factor code"));
+        }
+
+        self.db_client
+            .consume_user_token(UserTokenKind::MfaPending, &mfa_token_hash)
+            .await
+            .map_err(|_| HttpError::server_error("failed to consume mfa token"))?
+            .ok_or_else(|| HttpError::unauthorized("invalid or expired mfa token"))?;
+
+        self.login_throttle.clear(&email_key).await?;
+
+        self.check_sign_in(&user)?;
+
+        self.start_session(&user, client).await
+    }
+
+    pub async fn forgot_password(&self, data: ForgotPasswordDto) -> Result<Response, HttpError> {
+        let user = self
+            .db_client
+            .get_user(None, None, Some(&data.email))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check existing user"))?;
+
+        // issuing the token and sending the mail happen off the request, so known and unknown
+        // emails take the same time to answer
+        if let Some(user) = user {
+            let auth_service = self.clone();
+
+            spawn(async move {
+                if let Err(e) = auth_service.send_password_reset_email(&user).await {
+                    eprintln!("Failed to send password reset email: {e}");
+                }
+            });
+        }
+
+        let body = Json(json!({
+            "message": "If the account exists, a password reset email has been sent",
+        }));
+
+        Ok((StatusCode::ACCEPTED, body).into_response())
+    }
+
+    pub async fn reset_password(&self, data: ResetPasswordDto) -> Result<Response, HttpError> {
+        let token = self
+            .db_client
+            .consume_user_token(UserTokenKind::PasswordReset, &hash_token(&data.token))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check password reset token"))?
+            .ok_or_else(|| HttpError::bad_request("invalid or expired password reset token"))?;
+
+        let password = PasswordArgon::hash(&data.password)
+            .map_err(|_| HttpError::server_error("failed to hash password"))?;
+
+        self.db_client
+            .update_user(token.user_id, None, Some(password), None)
+            .await
+            .map_err(|_| HttpError::server_error("failed to update password"))?
+            .ok_or_else(|| HttpError::not_found("user no longer exists"))?;
+
+        self.db_client
+            .delete_user_tokens(token.user_id, UserTokenKind::PasswordReset)
+            .await
+            .map_err(|_| HttpError::server_error("failed to clear password reset tokens"))?;
+
+        self.db_client
+            .revoke_user_sessions(token.user_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke sessions"))?;
+
+        let body = Json(json!({
+            "message": "Password has been reset",
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn logout(&self, cookie: Cookie<'_>) -> Result<Response, HttpError> {
+        if cookie.name() != "refresh_token" {
+            return Err(HttpError::bad_request("refresh token not found"));
+        }
+
+        let stored_token = self
+            .db_client
+            .get_refresh_token(&hash_token(cookie.value()))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check refresh token"))?;
+
+        if let Some(stored_token) = stored_token {
+            self.revoke_family(stored_token.family_id).await?;
+        }
+
+        let cookie_empty = Cookie::build(("refresh_token", ""))
+            .http_only(true)
+            //.secure(true)
+            .path("/")
+            .build();
+
+        let mut headers = HeaderMap::new();
+        let cookie_value = HeaderValue::from_str(&cookie_empty.to_string())
+            .map_err(|_| HttpError::server_error("failed to set empty refresh token cookie"))?;
+
+        headers.append(header::SET_COOKIE, cookie_value);
+
+        Ok((StatusCode::OK, headers).into_response())
+    }
+
+    pub async fn enroll_mfa(&self, user: &User) -> Result<Response, HttpError> {
+        if user.totp_enabled_at.is_some() {
+            return Err(HttpError::unique_constraint_violation(
+                "two-factor authentication is already enabled",
+            ));
+        }
+
+        let secret = Totp::generate_secret();
+        let otpauth_uri = Totp::otpauth_uri(&secret, &user.email)
+            .map_err(|e| HttpError::server_error(e.to_string()))?;
+
+        self.db_client
+            .set_totp_secret(user.id, secret.clone())
+            .await
+            .map_err(|_| HttpError::server_error("failed to store totp secret"))?
+            .ok_or_else(|| {
+                HttpError::unique_constraint_violation(
+                    "two-factor authentication is already enabled",
+                )
+            })?;
+
+        let body = Json(json!({
+            "secret": secret,
+            "otpauth_uri": otpauth_uri,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn confirm_mfa(&self, user: &User, data: MfaCodeDto) -> Result<Response, HttpError> {
+        if user.totp_enabled_at.is_some() {
+            return Err(HttpError::unique_constraint_violation(
+                "two-factor authentication is already enabled",
+            ));
+        }
+
+        let secret = user
+            .totp_secret
+            .as_deref()
+            .ok_or_else(|| HttpError::bad_request("two-factor enrolment was not started"))?;
+
+        let totp_step = Totp::verify(secret, &data.code)
+            .map_err(|e| HttpError::server_error(e.to_string()))?
+            .ok_or_else(|| HttpError::bad_request("invalid two-factor code"))?;
+
+        let recovery_codes = Totp::generate_recovery_codes();
+        let recovery_code_hashes = recovery_codes
+            .iter()
+            .map(|code| hash_token(&Totp::normalize_recovery_code(code)))
+            .collect();
+
+        self.db_client
+            .enable_totp(user.id, totp_step as i64, recovery_code_hashes)
+            .await
+            .map_err(|_| HttpError::server_error("failed to enable two-factor authentication"))?
+            .ok_or_else(|| HttpError::bad_request("two-factor enrolment was not started"))?;
+
+        let body = Json(json!({
+            "message": "Two-factor authentication enabled",
+            "recovery_codes": recovery_codes,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn disable_mfa(&self, user: &User, data: MfaCodeDto) -> Result<Response, HttpError> {
+        if user.totp_enabled_at.is_none() {
+            return Err(HttpError::bad_request(
+                "two-factor authentication is not enabled",
+            ));
+        }
+
+        let mfa_required = self
+            .db_client
+            .is_mfa_required(&user.role)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check mfa policy"))?;
+
+        if mfa_required {
+            return Err(HttpError::forbidden(
+                "two-factor authentication is required for your role",
+            ));
+        }
+
+        if !self.check_mfa_code(user, &data.code).await? {
+            return Err(HttpError::bad_request("invalid two-factor code"));
+        }
+
+        self.db_client
+            .disable_totp(user.id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to disable two-factor authentication"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn get_sessions(&self, user_id: Uuid) -> Result<Response, HttpError> {
+        let sessions = self
+            .db_client
+            .get_sessions(user_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get sessions"))?;
+
+        let body = Json(json!({
+            "sessions": sessions,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn revoke_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<Response, HttpError> {
+        let revoked = self
+            .db_client
+            .revoke_session(user_id, session_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke session"))?;
+
+        if !revoked {
+            return Err(HttpError::not_found(format!(
+                "session with id: {session_id} not found"
+            )));
+        }
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    async fn complete_login(&self, user: &User, client: ClientInfo) -> Result<Response, HttpError> {
+        self.check_sign_in(user)?;
+
+        if user.totp_enabled_at.is_some() {
+            let mfa_token = self
+                .issue_user_token(
+                    user,
+                    UserTokenKind::MfaPending,
+                    self.env.mfa_pending_expires,
+                )
+                .await?;
+
+            let body = Json(json!({
+                "message": "Two-factor authentication required",
+                "mfa_required": true,
+                "mfa_token": mfa_token,
+            }));
+
+            return Ok((StatusCode::OK, body).into_response());
+        }
+
+        self.start_session(user, client).await
+    }
+
+    async fn find_or_create_oauth_user(
+        &self,
+        provider: &OAuthProvider,
+        profile: OAuthProfile,
+        link_user_id: Option<Uuid>,
+    ) -> Result<User, HttpError> {
+        let linked_user = self
+            .db_client
+            .get_oauth_user(&provider.name, &profile.subject)
+            .await
+            .map_err(|_| HttpError::server_error("failed to check oauth identity"))?;
+
+        if let Some(user) = linked_user {
+            if link_user_id.is_some_and(|user_id| user_id != user.id) {
+                return Err(HttpError::unique_constraint_violation(
+                    "the provider account is already linked to another user",
+                ));
+            }
+
+            return Ok(user);
+        }
+
+        // the login was started by a signed in user, link the provider account to it
+        if let Some(user_id) = link_user_id {
+            let user = self
+                .db_client
+                .get_user(Some(user_id), None, None)
+                .await
+                .map_err(|_| HttpError::server_error("failed to check existing user"))?
+                .ok_or_else(|| HttpError::unauthorized("user no longer exists"))?;
+
+            let email = profile.email.filter(|_| profile.email_verified);
+
+            self.db_client
+                .link_oauth_identity(user.id, &provider.name, &profile.subject, email)
+                .await
+                .map_err(|_| HttpError::server_error("failed to link oauth identity"))?;
+
+            return Ok(user);
+        }
+
+        // only an email the provider verified may be matched to an account,
+        // anything else has to be linked by signing in first
+        let email = profile
+            .email
+            .filter(|_| profile.email_verified)
+            .ok_or_else(|| {
+                HttpError::unauthorized(
+                    "the oauth provider did not return a verified email, sign in to link this account",
+                )
+            })?;
+
+        let found_user = self
+            .db_client
+            .get_user(None, None, Some(&email))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check existing user"))?;
+
+        let user = match found_user {
+            // the provider proved the email, drop any password set while it was unverified
+            Some(user) if user.email_verified_at.is_none() => {
+                let password = PasswordArgon::hash(generate_token())
+                    .map_err(|_| HttpError::server_error("failed to hash password"))?;
+
+                let user = self
+                    .db_client
+                    .update_user(user.id, None, Some(password), None)
+                    .await
+                    .map_err(|_| HttpError::server_error("failed to update user"))?
+                    .ok_or_else(|| HttpError::server_error("user no longer exists"))?;
+
+                self.db_client
+                    .revoke_user_sessions(user.id)
+                    .await
+                    .map_err(|_| HttpError::server_error("failed to revoke sessions"))?;
+
+                user
+            }
+            Some(user) => user,
+            None => {
+                let name = profile
+                    .name
+                    .filter(|name| !name.trim().is_empty())
+                    .unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string());
+
+                let password = PasswordArgon::hash(generate_token())
+                    .map_err(|_| HttpError::server_error("failed to hash password"))?;
+
+                self.db_client
+                    .create_user(name.chars().take(100).collect(), email.clone(), password)
+                    .await
+                    .map_err(|_| HttpError::server_error("failed to create user"))?
+            }
+        };
+
+        let user = match user.email_verified_at {
+            Some(_) => user,
+            None => self
+                .db_client
+                .verify_user_email(user.id)
+                .await
+                .map_err(|_| HttpError::server_error("failed to verify email"))?
+                .ok_or_else(|| HttpError::server_error("user no longer exists"))?,
+        };
+
+        self.db_client
+            .link_oauth_identity(user.id, &provider.name, &profile.subject, Some(email))
+            .await
+            .map_err(|_| HttpError::server_error("failed to link oauth identity"))?;
+
+        Ok(user)
+    }
+
+    // accounts the user deleted within the grace period can still sign in, which
+    // restores them, accounts deleted by someone else stay deleted
+    fn check_sign_in(&self, user: &User) -> Result<(), HttpError> {
+        if user.status != UserStatus::Deactivated {
+            return check_account_status(user);
+        }
+
+        let grace_period = TimeDelta::days(self.env.account_deletion_grace_days);
+
+        match user.deleted_at {
+            Some(deleted_at)
+                if user.deleted_by == Some(user.id) && deleted_at + grace_period > Utc::now() =>
+            {
+                Ok(())
+            }
+            _ => Err(HttpError::unauthorized("account has been deleted")),
+        }
+    }
+
+    async fn start_session(&self, user: &User, client: ClientInfo) -> Result<Response, HttpError> {
+        let user = &match user.status {
+            UserStatus::Deactivated => self
+                .db_client
+                .reactivate_user(user.id, self.env.account_deletion_grace_days)
+                .await
+                .map_err(|_| HttpError::server_error("failed to restore account"))?
+                .ok_or_else(|| HttpError::unauthorized("account has been deleted"))?,
+            _ => user.clone(),
+        };
+
+        // a suspension set before the deletion applies again once restored
+        check_account_status(user)?;
+
+        let user_response = UserResponseDto::from_user(user);
 
         let access_token = TokenClaims::encode(
-            &user.id.to_string(),
-            self.env.jwt_access_token_secert.as_bytes(),
+            user,
+            TokenType::Access,
+            &self.access_keys,
             self.env.jwt_access_token_expires,
         )
         .map_err(|_| HttpError::server_error("failed to generate access token"))?;
 
+        let cookie = self
+            .issue_refresh_token(user, Uuid::new_v4(), client)
+            .await?;
+
+        let headers = Self::token_headers(&access_token, &cookie)?;
+
+        let body = Json(json!({
+            "message": "Login successful",
+            "user": user_response,
+        }));
+
+        Ok((StatusCode::OK, headers, body).into_response())
+    }
+
+    async fn check_mfa_code(&self, user: &User, code: &str) -> Result<bool, HttpError> {
+        let Some(secret) = user.totp_secret.as_deref() else {
+            return Ok(false);
+        };
+
+        if let Some(totp_step) =
+            Totp::verify(secret, code).map_err(|e| HttpError::server_error(e.to_string()))?
+        {
+            return self
+                .db_client
+                .accept_totp_step(user.id, totp_step as i64)
+                .await
+                .map_err(|_| HttpError::server_error("failed to check two-factor code"));
+        }
+
+        self.db_client
+            .consume_recovery_code(user.id, &hash_token(&Totp::normalize_recovery_code(code)))
+            .await
+            .map_err(|_| HttpError::server_error("failed to check recovery code"))
+    }
+
+    async fn issue_refresh_token(
+        &self,
+        user: &User,
+        family_id: Uuid,
+        client: ClientInfo,
+    ) -> Result<Cookie<'static>, HttpError> {
         let refresh_token = TokenClaims::encode(
-            &user.id.to_string(),
-            self.env.jwt_refresh_token_secert.as_bytes(),
+            user,
+            TokenType::Refresh,
+            &self.refresh_keys,
             self.env.jwt_refresh_token_expires,
         )
         .map_err(|_| HttpError::server_error("failed to generate refresh token"))?;
 
+        let expires_at = Utc::now() + TimeDelta::seconds(self.env.jwt_refresh_token_expires);
+
+        self.db_client
+            .create_refresh_token(
+                user.id,
+                family_id,
+                hash_token(&refresh_token),
+                expires_at,
+                client,
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to store refresh token"))?;
+
         let cookie = Cookie::build(("refresh_token", refresh_token))
             .http_only(true)
             //.secure(true)
@@ -119,6 +892,90 @@ impl AuthService {
             .max_age(Duration::seconds(self.env.jwt_refresh_token_expires))
             .build();
 
+        Ok(cookie)
+    }
+
+    async fn send_verification_email(&self, user: &User) -> Result<(), HttpError> {
+        let token = self
+            .issue_user_token(
+                user,
+                UserTokenKind::EmailVerification,
+                self.env.email_verification_expires,
+            )
+            .await?;
+
+        let mail = verification_mail(&user.email, &user.name, &self.env.app_url, &token);
+
+        self.mailer
+            .send(mail)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))
+    }
+
+    async fn send_password_reset_email(&self, user: &User) -> Result<(), HttpError> {
+        let token = self
+            .issue_user_token(
+                user,
+                UserTokenKind::PasswordReset,
+                self.env.password_reset_expires,
+            )
+            .await?;
+
+        let mail = password_reset_mail(
+            &user.email,
+            &user.name,
+            self.env.password_reset_url.as_deref(),
+            &token,
+        );
+
+        self.mailer
+            .send(mail)
+            .await
+            .map_err(|e| HttpError::server_error(e.to_string()))
+    }
+
+    async fn issue_user_token(
+        &self,
+        user: &User,
+        kind: UserTokenKind,
+        expires_in_sec: i64,
+    ) -> Result<String, HttpError> {
+        self.db_client
+            .delete_user_tokens(user.id, kind)
+            .await
+            .map_err(|_| HttpError::server_error("failed to clear user tokens"))?;
+
+        let token = generate_token();
+        let expires_at = Utc::now() + TimeDelta::seconds(expires_in_sec);
+
+        self.db_client
+            .create_user_token(user.id, kind, hash_token(&token), expires_at)
+            .await
+            .map_err(|_| HttpError::server_error("failed to store user token"))?;
+
+        Ok(token)
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> Result<(), HttpError> {
+        self.db_client
+            .revoke_token_family(family_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke refresh tokens"))?;
+
+        Ok(())
+    }
+
+    fn oauth_state_cookie(value: String, max_age: Duration) -> Cookie<'static> {
+        Cookie::build((OAUTH_STATE_COOKIE, value))
+            .http_only(true)
+            //.secure(true)
+            .same_site(SameSite::Lax)
+            .path(format!("{}{}/oauth", Routes::Base, Routes::Auth))
+            .max_age(max_age)
+            .build()
+    }
+
+    fn token_headers(access_token: &str, cookie: &Cookie) -> Result<HeaderMap, HttpError> {
         let mut headers = HeaderMap::new();
 
         let auth_value = HeaderValue::from_str(&format!("Bearer {access_token}"))
@@ -129,31 +986,93 @@ impl AuthService {
             .map_err(|_| HttpError::server_error("failed to set refresh token cookie"))?;
         headers.append(header::SET_COOKIE, cookie_value);
 
-        let body = Json(json!({
-            "message": "Login successful",
-            "user": user_response,
-        }));
+        Ok(headers)
+    }
+}
 
-        Ok((StatusCode::OK, headers, body).into_response())
+fn verification_mail(email: &str, name: &str, app_url: &str, token: &str) -> Mail {
+    Mail {
+        to: email.to_string(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Hi {name},\n\nConfirm your email address by opening the link below:\n{app_url}{}{}/verify-email?token={token}\n\nOr send this token to POST /api/v1/auth/verify-email: {token}\n",
+            Routes::Base,
+            Routes::Auth
+        ),
+    }
+}
+
+// the reset endpoint needs the new password, so the mail can only link to a
+// frontend page, without one it just carries the token
+fn password_reset_mail(email: &str, name: &str, reset_url: Option<&str>, token: &str) -> Mail {
+    let endpoint = format!("POST {}{}/reset-password", Routes::Base, Routes::Auth);
+    let instructions = match reset_url {
+        Some(url) => format!(
+            "Reset your password by opening the link below:\n{url}?token={token}\n\nOr send this token to {endpoint}: {token}"
+        ),
+        None => format!("Reset your password by sending this token to {endpoint}: {token}"),
+    };
+
+    Mail {
+        to: email.to_string(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "Hi {name},\n\n{instructions}\n\nIf you did not request a password reset, you can ignore this email.\n"
+        ),
     }
+}
 
-    pub fn logout(&self, cookie: Cookie) -> Result<Response, HttpError> {
-        if cookie.name() != "refresh_token" {
-            return Err(HttpError::bad_request("refresh token not found"));
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::mailer::{Mailer, MemoryMailer};
 
-        let cookie_empty = Cookie::build(("refresh_token", ""))
-            .http_only(true)
-            //.secure(true)
-            .path("/")
-            .build();
+    #[tokio::test]
+    async fn verification_mail_links_to_the_verify_email_endpoint() {
+        let mailer = MemoryMailer::new();
+        let mail = verification_mail("ann@example.com", "Ann", "http://localhost:8000", "abc123");
 
-        let mut headers = HeaderMap::new();
-        let cookie_value = HeaderValue::from_str(&cookie_empty.to_string())
-            .map_err(|_| HttpError::server_error("failed to set empty refresh token cookie"))?;
+        mailer.send(mail).await.unwrap();
 
-        headers.append(header::SET_COOKIE, cookie_value);
+        let outbox = mailer.outbox();
+        assert_eq!(outbox.len(), 1);
+        assert_eq!(outbox[0].to, "ann@example.com");
+        assert_eq!(outbox[0].subject, "Verify your email address");
+        assert!(
+            outbox[0]
+                .body
+                .contains("http://localhost:8000/api/v1/auth/verify-email?token=abc123")
+        );
+    }
 
-        Ok((StatusCode::OK, headers).into_response())
+    #[test]
+    fn password_reset_mail_links_to_the_configured_page() {
+        let mail = password_reset_mail(
+            "ann@example.com",
+            "Ann",
+            Some("https://app.example.com/reset-password"),
+            "abc123",
+        );
+
+        assert_eq!(mail.to, "ann@example.com");
+        assert_eq!(mail.subject, "Reset your password");
+        assert!(
+            mail.body
+                .contains("https://app.example.com/reset-password?token=abc123")
+        );
+        assert!(
+            mail.body
+                .contains("POST /api/v1/auth/reset-password: abc123")
+        );
+    }
+
+    #[test]
+    fn password_reset_mail_without_a_page_only_carries_the_token() {
+        let mail = password_reset_mail("ann@example.com", "Ann", None, "abc123");
+
+        assert!(!mail.body.contains("?token="));
+        assert!(mail.body.contains(
+            "Reset your password by sending this token to POST /api/v1/auth/reset-password: abc123"
+        ));
     }
 }
@@ -0,0 +1,135 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    db::{DBClient, comments_db::CommentExt},
+    dtos::{
+        comment_dto::{CommentNode, CommentsView, CreateCommentDto},
+        user_dto::User,
+    },
+    error::HttpError,
+    services::posts_service::get_readable_post,
+};
+
+#[derive(Debug, Clone)]
+pub struct CommentsService {
+    db_client: DBClient,
+}
+
+impl CommentsService {
+    pub fn new(db_client: DBClient) -> Self {
+        Self { db_client }
+    }
+
+    pub async fn get_comments(
+        &self,
+        post_id: Uuid,
+        viewer: Option<User>,
+        view: CommentsView,
+        page: usize,
+        limit: usize,
+    ) -> Result<Response, HttpError> {
+        get_readable_post(&self.db_client, post_id, viewer.as_ref()).await?;
+
+        let comment_count = self
+            .db_client
+            .get_comments_count(post_id, view == CommentsView::Tree)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get comment count"))?;
+
+        // in the tree view pages are made of top level comments, each with all of its replies
+        let comments = match view {
+            CommentsView::Tree => json!(CommentNode::build_tree(
+                self.db_client
+                    .get_comment_threads(post_id, page, limit)
+                    .await
+                    .map_err(|_| HttpError::server_error("failed to get comments"))?,
+            )),
+            CommentsView::Flat => json!(
+                self.db_client
+                    .get_comments(post_id, page, limit)
+                    .await
+                    .map_err(|_| HttpError::server_error("failed to get comments"))?
+            ),
+        };
+
+        let body = Json(json!({
+            "comments": comments,
+            "view": view,
+            "total": comment_count,
+            "limit": limit,
+            "page": page,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn create_comment(
+        &self,
+        post_id: Uuid,
+        user: &User,
+        data: CreateCommentDto,
+    ) -> Result<Response, HttpError> {
+        get_readable_post(&self.db_client, post_id, Some(user)).await?;
+
+        if let Some(parent_id) = data.parent_id {
+            self.db_client
+                .get_comment(parent_id)
+                .await
+                .map_err(|_| HttpError::server_error("failed to get comment"))?
+                .filter(|parent| parent.post_id == post_id)
+                .ok_or_else(|| {
+                    HttpError::bad_request("parent_id must reference a comment on this post")
+                })?;
+        }
+
+        let comment = self
+            .db_client
+            .create_comment(post_id, user.id, data.parent_id, data.body)
+            .await
+            .map_err(|_| HttpError::server_error("failed to create comment"))?;
+
+        let body = Json(json!({
+            "comment": comment,
+        }));
+
+        Ok((StatusCode::CREATED, body).into_response())
+    }
+
+    pub async fn update_comment(
+        &self,
+        post_id: Uuid,
+        id: Uuid,
+        body: String,
+    ) -> Result<Response, HttpError> {
+        self.db_client
+            .update_comment(post_id, id, body)
+            .await
+            .map_err(|_| HttpError::server_error("failed to update comment"))?
+            .ok_or_else(|| HttpError::not_found(format!("comment with id: {id} not found")))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    // replies are removed together with the comment
+    pub async fn delete_comment(&self, post_id: Uuid, id: Uuid) -> Result<Response, HttpError> {
+        let deleted = self
+            .db_client
+            .delete_comment(post_id, id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to delete comment"))?;
+
+        if !deleted {
+            return Err(HttpError::not_found(format!(
+                "comment with id: {id} not found"
+            )));
+        }
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+}
@@ -1,11 +1,15 @@
 pub mod auth_service;
+pub mod comments_service;
 pub mod posts_service;
+pub mod roles_service;
+pub mod tags_service;
 pub mod users_service;
 
 use crate::{
     AppState,
     services::{
-        auth_service::AuthService, posts_service::PostsService, users_service::UsersService,
+        auth_service::AuthService, comments_service::CommentsService, posts_service::PostsService,
+        roles_service::RolesService, tags_service::TagsService, users_service::UsersService,
     },
 };
 
@@ -14,6 +18,9 @@ pub struct Services {
     pub auth_service: AuthService,
     pub users_service: UsersService,
     pub posts_service: PostsService,
+    pub comments_service: CommentsService,
+    pub roles_service: RolesService,
+    pub tags_service: TagsService,
 }
 
 impl Services {
@@ -21,7 +28,13 @@ impl Services {
         Self {
             auth_service: AuthService::new(app_state.clone()),
             users_service: UsersService::new(app_state.db_client.clone()),
-            posts_service: PostsService::new(app_state.db_client),
+            posts_service: PostsService::new(
+                app_state.db_client.clone(),
+                app_state.env.post_reaction_kinds.clone(),
+            ),
+            comments_service: CommentsService::new(app_state.db_client.clone()),
+            roles_service: RolesService::new(app_state.db_client.clone()),
+            tags_service: TagsService::new(app_state.db_client),
         }
     }
 }
@@ -1,85 +1,146 @@
+use std::collections::BTreeMap;
+
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
-    db::{DBClient, posts_db::PostExt},
+    db::{
+        DBClient, post_revisions_db::PostRevisionExt, posts_db::PostExt, reactions_db::ReactionExt,
+        roles_db::RoleExt, tags_db::TagExt,
+    },
+    dtos::{
+        post_dto::{
+            CreatePostDto, Post, PostFilter, PostResponseDto, PostRevision, PostStatus,
+            ReactionSummary, UpdatePostDto,
+        },
+        role_dto::Permission,
+        tag_dto::normalize_tags,
+        user_dto::User,
+    },
     error::HttpError,
+    utils::{
+        diff::line_diff,
+        etag::{etag_header, etag_of, if_match, if_none_match},
+        pagination::{ListParams, Page},
+    },
 };
 
 #[derive(Debug, Clone)]
 pub struct PostsService {
     db_client: DBClient,
+    reaction_kinds: Vec<String>,
 }
 
 impl PostsService {
-    pub fn new(db_client: DBClient) -> Self {
-        Self { db_client }
+    pub fn new(db_client: DBClient, reaction_kinds: Vec<String>) -> Self {
+        Self {
+            db_client,
+            reaction_kinds,
+        }
     }
 
-    pub async fn get_post(&self, id: Uuid) -> Result<Response, HttpError> {
-        let post = self
-            .db_client
-            .get_post_by_id(id)
-            .await
-            .map_err(|_| HttpError::server_error("failed to get post"))?
-            .ok_or_else(|| HttpError::not_found(format!("post with id: {id} not found")))?;
+    pub async fn get_post(
+        &self,
+        id: Uuid,
+        viewer: Option<User>,
+        headers: &HeaderMap,
+    ) -> Result<Response, HttpError> {
+        let post = get_readable_post(&self.db_client, id, viewer.as_ref()).await?;
+
+        let viewer_id = viewer.map(|viewer| viewer.id);
+        let (post, etag) = self.post_representation(post, viewer_id).await?;
+
+        // `reacted` depends on the token, so shared caches must key on it
+        let vary = [(header::VARY, header::AUTHORIZATION.as_str())];
+
+        if if_none_match(headers, &etag) == Some(true) {
+            return Ok((StatusCode::NOT_MODIFIED, etag_header(&etag), vary).into_response());
+        }
 
         let body = Json(json!({
            "post" :post,
         }));
 
-        Ok((StatusCode::OK, body).into_response())
+        Ok((StatusCode::OK, etag_header(&etag), vary, body).into_response())
     }
 
     pub async fn get_posts_by_user_id(
         &self,
         user_id: Uuid,
-        page: usize,
-        limit: usize,
+        viewer: Option<User>,
+        mut filter: PostFilter,
+        params: ListParams,
     ) -> Result<Response, HttpError> {
-        let posts = self
-            .db_client
-            .get_posts_by_user_id(user_id, page, limit)
-            .await
-            .map_err(|_| HttpError::server_error("failed to get posts by user id"))?;
+        filter.author = Some(user_id);
 
-        let post_count = self
-            .db_client
-            .get_posts_count()
-            .await
-            .map_err(|_| HttpError::server_error("failed to get post count"))?;
+        let (posts, post_count) = self.list_posts(&filter, &params, viewer.as_ref()).await?;
+        let viewer_id = viewer.map(|viewer| viewer.id);
+        let items = self.to_responses(posts.items, viewer_id).await?;
 
         let body = Json(json!({
-            "posts": posts,
+            "posts": items,
             "form_user_id": user_id,
             "total": post_count,
-            "limit": limit,
-            "page": page,
+            "limit": params.limit,
+            "page": params.cursor.is_none().then_some(params.page),
+            "sort": params.sort.key(),
+            "next_cursor": posts.next_cursor,
+            "prev_cursor": posts.prev_cursor,
         }));
 
         Ok((StatusCode::OK, body).into_response())
     }
 
-    pub async fn get_posts(&self, page: usize, limit: usize) -> Result<Response, HttpError> {
+    pub async fn get_posts(
+        &self,
+        viewer: Option<User>,
+        filter: PostFilter,
+        params: ListParams,
+    ) -> Result<Response, HttpError> {
+        let (posts, post_count) = self.list_posts(&filter, &params, viewer.as_ref()).await?;
+        let viewer_id = viewer.map(|viewer| viewer.id);
+        let items = self.to_responses(posts.items, viewer_id).await?;
+
+        let body = Json(json!({
+            "posts": items,
+            "total": post_count,
+            "limit": params.limit,
+            "page": params.cursor.is_none().then_some(params.page),
+            "sort": params.sort.key(),
+            "next_cursor": posts.next_cursor,
+            "prev_cursor": posts.prev_cursor,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn search_posts(
+        &self,
+        search: &str,
+        page: usize,
+        limit: usize,
+    ) -> Result<Response, HttpError> {
         let posts = self
             .db_client
-            .get_posts(page, limit)
+            .search_posts(search, page, limit)
             .await
-            .map_err(|_| HttpError::server_error("failed to get posts"))?;
+            .map_err(|_| HttpError::server_error("failed to search posts"))?;
 
         let post_count = self
             .db_client
-            .get_posts_count()
+            .search_posts_count(search)
             .await
             .map_err(|_| HttpError::server_error("failed to get post count"))?;
 
         let body = Json(json!({
             "posts": posts,
+            "q": search,
             "total": post_count,
             "limit": limit,
             "page": page,
@@ -91,11 +152,16 @@ impl PostsService {
     pub async fn create_post(
         &self,
         user_id: Uuid,
-        title: String,
-        body: String,
+        mut data: CreatePostDto,
     ) -> Result<Response, HttpError> {
+        let status = data.status.unwrap_or_default();
+
+        data.tags = data.tags.map(|tags| normalize_tags(&tags));
+        data.published_at = Self::published_at(Some(status), data.published_at, None)?;
+        data.status = Some(status);
+
         self.db_client
-            .create_post(user_id, title, body)
+            .create_post(user_id, data)
             .await
             .map_err(|_| HttpError::server_error("failed to create post"))?;
 
@@ -105,23 +171,580 @@ impl PostsService {
     pub async fn update_post(
         &self,
         id: Uuid,
-        title: Option<String>,
-        body: Option<String>,
+        editor_id: Uuid,
+        mut data: UpdatePostDto,
+        headers: &HeaderMap,
     ) -> Result<Response, HttpError> {
-        self.db_client
-            .update_post(id, title, body)
+        let post = self
+            .db_client
+            .get_post_by_id(id)
             .await
-            .map_err(|_| HttpError::server_error("failed to update post"))?;
+            .map_err(|_| HttpError::server_error("failed to get post"))?
+            .ok_or_else(|| HttpError::not_found(format!("post with id: {id} not found")))?;
 
-        Ok((StatusCode::NO_CONTENT).into_response())
+        let expected_updated_at = match headers.contains_key(header::IF_MATCH) {
+            true => {
+                let updated_at = post.updated_at;
+                let (_, etag) = self
+                    .post_representation(post.clone(), Some(editor_id))
+                    .await?;
+
+                if if_match(headers, &etag) != Some(true) {
+                    return Err(HttpError::precondition_failed(
+                        "post has been modified since it was fetched",
+                    ));
+                }
+
+                Some(updated_at)
+            }
+            false => None,
+        };
+
+        data.tags = data.tags.map(|tags| normalize_tags(&tags));
+        data.published_at = Self::published_at(data.status, data.published_at, Some(&post))?;
+
+        let post = self
+            .db_client
+            .update_post(id, editor_id, data, None, expected_updated_at)
+            .await
+            .map_err(|_| HttpError::server_error("failed to update post"))?
+            .ok_or_else(|| match expected_updated_at {
+                Some(_) => {
+                    HttpError::precondition_failed("post has been modified since it was fetched")
+                }
+                None => HttpError::not_found(format!("post with id: {id} not found")),
+            })?;
+        let (_, etag) = self.post_representation(post, Some(editor_id)).await?;
+
+        Ok((StatusCode::NO_CONTENT, etag_header(&etag)).into_response())
     }
 
     pub async fn delete_post(&self, id: Uuid) -> Result<Response, HttpError> {
-        self.db_client
+        let deleted = self
+            .db_client
             .delete_post(id)
             .await
             .map_err(|_| HttpError::server_error("failed to delete post"))?;
 
+        if !deleted {
+            return Err(HttpError::not_found(format!(
+                "post with id: {id} not found"
+            )));
+        }
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn add_reaction(
+        &self,
+        post_id: Uuid,
+        user: &User,
+        kind: &str,
+    ) -> Result<Response, HttpError> {
+        self.ensure_reaction_target(post_id, user, kind).await?;
+
+        self.db_client
+            .add_reaction(post_id, user.id, kind)
+            .await
+            .map_err(|_| HttpError::server_error("failed to add reaction"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn remove_reaction(
+        &self,
+        post_id: Uuid,
+        user: &User,
+        kind: &str,
+    ) -> Result<Response, HttpError> {
+        self.ensure_reaction_target(post_id, user, kind).await?;
+
+        self.db_client
+            .remove_reaction(post_id, user.id, kind)
+            .await
+            .map_err(|_| HttpError::server_error("failed to remove reaction"))?;
+
         Ok((StatusCode::NO_CONTENT).into_response())
     }
+
+    pub async fn get_post_revisions(
+        &self,
+        post_id: Uuid,
+        page: usize,
+        limit: usize,
+    ) -> Result<Response, HttpError> {
+        let revisions = self
+            .db_client
+            .get_post_revisions(post_id, page, limit)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get post revisions"))?;
+
+        let revision_count = self
+            .db_client
+            .get_post_revisions_count(post_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get post revision count"))?;
+
+        let body = Json(json!({
+            "revisions": revisions,
+            "total": revision_count,
+            "limit": limit,
+            "page": page,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn get_post_revision(
+        &self,
+        post_id: Uuid,
+        revision: i32,
+    ) -> Result<Response, HttpError> {
+        let revision = self.find_revision(post_id, revision).await?;
+
+        let body = Json(json!({
+            "revision": revision,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn diff_post_revisions(
+        &self,
+        post_id: Uuid,
+        from: i32,
+        to: i32,
+    ) -> Result<Response, HttpError> {
+        let old = self.find_revision(post_id, from).await?;
+        let new = self.find_revision(post_id, to).await?;
+
+        let body = Json(json!({
+            "post_id": post_id,
+            "from": from,
+            "to": to,
+            "title": line_diff(&old.title, &new.title),
+            "body": line_diff(&old.body, &new.body),
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn revert_post(
+        &self,
+        post_id: Uuid,
+        editor_id: Uuid,
+        revision: i32,
+    ) -> Result<Response, HttpError> {
+        let revision = self.find_revision(post_id, revision).await?;
+
+        // revisions only cover the title and body, tags and status stay as they are
+        let post = self
+            .db_client
+            .update_post(
+                post_id,
+                editor_id,
+                UpdatePostDto {
+                    title: Some(revision.title),
+                    body: Some(revision.body),
+                    tags: None,
+                    status: None,
+                    published_at: None,
+                },
+                Some(revision.revision),
+                None,
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to revert post"))?
+            .ok_or_else(|| HttpError::not_found(format!("post with id: {post_id} not found")))?;
+        let post = self.to_responses(vec![post], Some(editor_id)).await?.pop();
+
+        let body = Json(json!({
+            "post": post,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn restore_post(&self, id: Uuid) -> Result<Response, HttpError> {
+        let post = self
+            .db_client
+            .restore_post(id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to restore post"))?
+            .ok_or_else(|| HttpError::not_found(format!("deleted post with id: {id} not found")))?;
+        let post = self.to_responses(vec![post], None).await?.pop();
+
+        let body = Json(json!({
+            "post": post,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    // users allowed to delete any post see the whole trash, everyone else only their own posts
+    pub async fn get_trash(
+        &self,
+        user: &User,
+        page: usize,
+        limit: usize,
+    ) -> Result<Response, HttpError> {
+        let can_delete_any = self
+            .db_client
+            .role_has_permission(&user.role, &Permission::PostsDeleteAny.to_string())
+            .await
+            .map_err(|_| HttpError::server_error("failed to check user permissions"))?;
+
+        let user_id = (!can_delete_any).then_some(user.id);
+
+        let posts = self
+            .db_client
+            .get_deleted_posts(user_id, page, limit)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get deleted posts"))?;
+
+        let post_count = self
+            .db_client
+            .get_deleted_posts_count(user_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get deleted post count"))?;
+
+        let posts = self.to_responses(posts, Some(user.id)).await?;
+
+        let body = Json(json!({
+            "posts": posts,
+            "total": post_count,
+            "limit": limit,
+            "page": page,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    async fn find_revision(&self, post_id: Uuid, revision: i32) -> Result<PostRevision, HttpError> {
+        self.db_client
+            .get_post_revision(post_id, revision)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get post revision"))?
+            .ok_or_else(|| {
+                HttpError::not_found(format!(
+                    "revision {revision} of post with id: {post_id} not found"
+                ))
+            })
+    }
+
+    async fn list_posts(
+        &self,
+        filter: &PostFilter,
+        params: &ListParams,
+        viewer: Option<&User>,
+    ) -> Result<(Page<Post>, i64), HttpError> {
+        if filter.status != PostStatus::Published
+            && !can_read_unpublished(&self.db_client, filter.author, viewer).await?
+        {
+            return Err(HttpError::forbidden(
+                "you are not allowed to list unpublished posts",
+            ));
+        }
+
+        let post_count = self
+            .db_client
+            .get_posts_count(filter)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get post count"))?;
+
+        let posts = self
+            .db_client
+            .get_posts(filter, params)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get posts"))?;
+
+        Ok((params.to_page(posts, post_count), post_count))
+    }
+
+    async fn ensure_reaction_target(
+        &self,
+        post_id: Uuid,
+        user: &User,
+        kind: &str,
+    ) -> Result<(), HttpError> {
+        if !self.reaction_kinds.iter().any(|allowed| allowed == kind) {
+            return Err(HttpError::bad_request(format!(
+                "reaction must be one of: {}",
+                self.reaction_kinds.join(", ")
+            )));
+        }
+
+        get_readable_post(&self.db_client, post_id, Some(user)).await?;
+
+        Ok(())
+    }
+
+    // `published_at` to store for a status change, `None` keeps the current value
+    fn published_at(
+        status: Option<PostStatus>,
+        published_at: Option<DateTime<Utc>>,
+        current: Option<&Post>,
+    ) -> Result<Option<DateTime<Utc>>, HttpError> {
+        let now = Utc::now();
+        let next_status = status
+            .or(current.map(|post| post.status))
+            .unwrap_or_default();
+
+        if next_status == PostStatus::Scheduled {
+            return match published_at {
+                Some(published_at) if published_at > now => Ok(Some(published_at)),
+                Some(_) => Err(HttpError::bad_request("published_at must be in the future")),
+                None if status.is_none() => Ok(None),
+                None => Err(HttpError::bad_request(
+                    "published_at is required to schedule a post",
+                )),
+            };
+        }
+
+        if published_at.is_some() {
+            return Err(HttpError::bad_request(
+                "published_at can only be set on scheduled posts",
+            ));
+        }
+
+        // publishing keeps the original date of a post that has been live before
+        match (status.unwrap_or(next_status), current) {
+            (PostStatus::Published, Some(post))
+                if post.status != PostStatus::Scheduled
+                    && post.published_at.is_some_and(|at| at <= now) =>
+            {
+                Ok(None)
+            }
+            (PostStatus::Published, _) => Ok(Some(now)),
+            _ => Ok(None),
+        }
+    }
+
+    // the etag covers the tags, the reactions and the viewer as well, none of
+    // them touch `updated_at`
+    async fn post_representation(
+        &self,
+        post: Post,
+        viewer_id: Option<Uuid>,
+    ) -> Result<(PostResponseDto, String), HttpError> {
+        let post = self
+            .to_responses(vec![post], viewer_id)
+            .await?
+            .pop()
+            .ok_or_else(|| HttpError::server_error("failed to build post response"))?;
+        let etag = etag_of(&(viewer_id, &post));
+
+        Ok((post, etag))
+    }
+
+    // every configured reaction kind is listed, counts come from the maintained totals instead of a COUNT per post
+    async fn to_responses(
+        &self,
+        posts: Vec<Post>,
+        viewer_id: Option<Uuid>,
+    ) -> Result<Vec<PostResponseDto>, HttpError> {
+        let post_ids: Vec<Uuid> = posts.iter().map(|post| post.id).collect();
+
+        let counts = self
+            .db_client
+            .get_reaction_counts(&post_ids)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get reactions"))?;
+
+        let tags = self
+            .db_client
+            .get_post_tags(&post_ids)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get post tags"))?;
+
+        let user_reactions = match viewer_id {
+            Some(viewer_id) => self
+                .db_client
+                .get_user_reactions(viewer_id, &post_ids)
+                .await
+                .map_err(|_| HttpError::server_error("failed to get reactions"))?,
+            None => Vec::new(),
+        };
+
+        let posts = posts
+            .into_iter()
+            .map(|post| {
+                let mut reactions: BTreeMap<String, ReactionSummary> = self
+                    .reaction_kinds
+                    .iter()
+                    .map(|kind| (kind.clone(), ReactionSummary::default()))
+                    .collect();
+
+                for count in counts.iter().filter(|count| count.post_id == post.id) {
+                    reactions.entry(count.kind.clone()).or_default().count = count.count;
+                }
+
+                for reaction in user_reactions.iter().filter(|r| r.post_id == post.id) {
+                    reactions.entry(reaction.kind.clone()).or_default().reacted = true;
+                }
+
+                let tags = tags
+                    .iter()
+                    .filter(|tag| tag.post_id == post.id)
+                    .map(|tag| tag.slug.clone())
+                    .collect();
+
+                PostResponseDto {
+                    post,
+                    tags,
+                    reactions,
+                }
+            })
+            .collect();
+
+        Ok(posts)
+    }
+}
+
+// unpublished posts look missing to everyone who may not read them
+pub async fn get_readable_post(
+    db_client: &DBClient,
+    id: Uuid,
+    viewer: Option<&User>,
+) -> Result<Post, HttpError> {
+    let post = db_client
+        .get_post_by_id(id)
+        .await
+        .map_err(|_| HttpError::server_error("failed to get post"))?
+        .ok_or_else(|| HttpError::not_found(format!("post with id: {id} not found")))?;
+
+    if post.status != PostStatus::Published
+        && !can_read_unpublished(db_client, Some(post.user_id), viewer).await?
+    {
+        return Err(HttpError::not_found(format!(
+            "post with id: {id} not found"
+        )));
+    }
+
+    Ok(post)
+}
+
+// authors read their own unpublished posts, other posts need the `posts:read:any` permission
+async fn can_read_unpublished(
+    db_client: &DBClient,
+    author: Option<Uuid>,
+    viewer: Option<&User>,
+) -> Result<bool, HttpError> {
+    let Some(viewer) = viewer else {
+        return Ok(false);
+    };
+
+    if author == Some(viewer.id) {
+        return Ok(true);
+    }
+
+    db_client
+        .role_has_permission(&viewer.role, &Permission::PostsReadAny.to_string())
+        .await
+        .map_err(|_| HttpError::server_error("failed to check user permissions"))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn post(status: PostStatus, published_at: Option<DateTime<Utc>>) -> Post {
+        Post {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            title: "Hello".to_string(),
+            body: "A post that is long enough".to_string(),
+            status,
+            published_at,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn is_recent(published_at: Option<DateTime<Utc>>) -> bool {
+        published_at.is_some_and(|at| Utc::now() - at < TimeDelta::seconds(5))
+    }
+
+    #[test]
+    fn drafts_have_no_publish_date() {
+        assert_eq!(
+            PostsService::published_at(Some(PostStatus::Draft), None, None).unwrap(),
+            None
+        );
+        assert_eq!(PostsService::published_at(None, None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn publishing_stamps_the_current_time() {
+        let published_at =
+            PostsService::published_at(Some(PostStatus::Published), None, None).unwrap();
+        assert!(is_recent(published_at));
+
+        let draft = post(PostStatus::Draft, None);
+        let published_at =
+            PostsService::published_at(Some(PostStatus::Published), None, Some(&draft)).unwrap();
+        assert!(is_recent(published_at));
+    }
+
+    #[test]
+    fn republishing_keeps_the_original_date() {
+        let archived = post(PostStatus::Archived, Some(Utc::now() - TimeDelta::days(30)));
+
+        assert_eq!(
+            PostsService::published_at(Some(PostStatus::Published), None, Some(&archived)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn publishing_a_scheduled_post_early_moves_its_date_to_now() {
+        let scheduled = post(PostStatus::Scheduled, Some(Utc::now() + TimeDelta::days(1)));
+
+        let published_at =
+            PostsService::published_at(Some(PostStatus::Published), None, Some(&scheduled))
+                .unwrap();
+        assert!(is_recent(published_at));
+    }
+
+    #[test]
+    fn scheduling_needs_a_future_date() {
+        let future = Utc::now() + TimeDelta::hours(1);
+        let past = Utc::now() - TimeDelta::hours(1);
+
+        assert_eq!(
+            PostsService::published_at(Some(PostStatus::Scheduled), Some(future), None).unwrap(),
+            Some(future)
+        );
+        assert!(PostsService::published_at(Some(PostStatus::Scheduled), Some(past), None).is_err());
+        assert!(PostsService::published_at(Some(PostStatus::Scheduled), None, None).is_err());
+    }
+
+    #[test]
+    fn scheduled_posts_keep_or_move_their_date() {
+        let scheduled = post(PostStatus::Scheduled, Some(Utc::now() + TimeDelta::days(1)));
+        let later = Utc::now() + TimeDelta::days(2);
+
+        assert_eq!(
+            PostsService::published_at(None, None, Some(&scheduled)).unwrap(),
+            None
+        );
+        assert_eq!(
+            PostsService::published_at(None, Some(later), Some(&scheduled)).unwrap(),
+            Some(later)
+        );
+    }
+
+    #[test]
+    fn only_scheduled_posts_take_a_date() {
+        let future = Utc::now() + TimeDelta::hours(1);
+        let draft = post(PostStatus::Draft, None);
+
+        assert!(
+            PostsService::published_at(Some(PostStatus::Published), Some(future), None).is_err()
+        );
+        assert!(PostsService::published_at(None, Some(future), Some(&draft)).is_err());
+    }
 }
@@ -0,0 +1,146 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::{
+    db::{DBClient, roles_db::RoleExt},
+    dtos::role_dto::{CreateRoleDto, Permission, UpdateRoleDto},
+    error::HttpError,
+};
+
+const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone)]
+pub struct RolesService {
+    db_client: DBClient,
+}
+
+impl RolesService {
+    pub fn new(db_client: DBClient) -> Self {
+        Self { db_client }
+    }
+
+    pub async fn get_roles(&self) -> Result<Response, HttpError> {
+        let roles = self
+            .db_client
+            .get_roles()
+            .await
+            .map_err(|_| HttpError::server_error("failed to get roles"))?;
+
+        let body = Json(json!({
+            "roles": roles,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn get_permissions(&self) -> Result<Response, HttpError> {
+        let permissions = self
+            .db_client
+            .get_permissions()
+            .await
+            .map_err(|_| HttpError::server_error("failed to get permissions"))?;
+
+        let body = Json(json!({
+            "permissions": permissions,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn create_role(&self, data: CreateRoleDto) -> Result<Response, HttpError> {
+        let role = self
+            .db_client
+            .create_role(
+                data.name.clone(),
+                data.description,
+                Self::permission_names(&data.permissions),
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to create role"))?
+            .ok_or_else(|| {
+                HttpError::unique_constraint_violation(format!(
+                    "role `{}` already exists",
+                    data.name
+                ))
+            })?;
+
+        let body = Json(json!({
+            "role": role,
+        }));
+
+        Ok((StatusCode::CREATED, body).into_response())
+    }
+
+    pub async fn update_role(
+        &self,
+        name: &str,
+        data: UpdateRoleDto,
+    ) -> Result<Response, HttpError> {
+        // admin keeps every permission so roles can always be managed
+        if name == ADMIN_ROLE && data.permissions.is_some() {
+            return Err(HttpError::bad_request(
+                "permissions of the admin role cannot be changed",
+            ));
+        }
+
+        let role = self
+            .db_client
+            .update_role(
+                name,
+                data.description,
+                data.permissions.as_deref().map(Self::permission_names),
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to update role"))?
+            .ok_or_else(|| HttpError::not_found(format!("role `{name}` not found")))?;
+
+        let body = Json(json!({
+            "role": role,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn delete_role(&self, name: &str) -> Result<Response, HttpError> {
+        let role = self
+            .db_client
+            .get_role(name)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get role"))?
+            .ok_or_else(|| HttpError::not_found(format!("role `{name}` not found")))?;
+
+        if role.is_system {
+            return Err(HttpError::bad_request("system roles cannot be deleted"));
+        }
+
+        let assigned = self
+            .db_client
+            .is_role_assigned(name)
+            .await
+            .map_err(|_| HttpError::server_error("failed to delete role"))?;
+
+        if assigned {
+            return Err(HttpError::unique_constraint_violation(format!(
+                "role `{name}` is still assigned to users"
+            )));
+        }
+
+        self.db_client
+            .delete_role(name)
+            .await
+            .map_err(|_| HttpError::server_error("failed to delete role"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    fn permission_names(permissions: &[Permission]) -> Vec<String> {
+        permissions
+            .iter()
+            .map(|permission| permission.to_string())
+            .collect()
+    }
+}
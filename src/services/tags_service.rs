@@ -0,0 +1,45 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+
+use crate::{
+    db::{DBClient, tags_db::TagExt},
+    error::HttpError,
+};
+
+#[derive(Debug, Clone)]
+pub struct TagsService {
+    db_client: DBClient,
+}
+
+impl TagsService {
+    pub fn new(db_client: DBClient) -> Self {
+        Self { db_client }
+    }
+
+    pub async fn get_tags(&self, page: usize, limit: usize) -> Result<Response, HttpError> {
+        let tags = self
+            .db_client
+            .get_tags(page, limit)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get tags"))?;
+
+        let tag_count = self
+            .db_client
+            .get_tags_count()
+            .await
+            .map_err(|_| HttpError::server_error("failed to get tag count"))?;
+
+        let body = Json(json!({
+            "tags": tags,
+            "total": tag_count,
+            "limit": limit,
+            "page": page,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+}
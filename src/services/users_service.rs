@@ -1,16 +1,31 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
+use chrono::Utc;
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
-    db::{DBClient, users_db::UserExt},
-    dtos::user_dto::{UpdateUserDto, UpdateUserRoleDto, UserResponseDto, UsersResponseDtoList},
+    db::{
+        DBClient, api_keys_db::ApiKeyExt, mfa_db::MfaExt, roles_db::RoleExt,
+        sessions_db::SessionExt, users_db::UserExt,
+    },
+    dtos::{
+        api_key_dto::CreateApiKeyDto,
+        user_dto::{
+            SuspendUserDto, UpdateMfaPolicyDto, UpdateUserDto, UpdateUserRoleDto, UserFilter,
+            UserResponseDto, UserStatus, UsersResponseDtoList,
+        },
+    },
     error::HttpError,
-    utils::password::PasswordArgon,
+    utils::{
+        etag::{etag, etag_header, if_match, if_none_match},
+        pagination::ListParams,
+        password::PasswordArgon,
+        token::{generate_api_key, hash_token},
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -23,49 +38,87 @@ impl UsersService {
         Self { db_client }
     }
 
-    pub async fn get_user(&self, id: Uuid) -> Result<Response, HttpError> {
+    pub async fn get_user(&self, id: Uuid, headers: &HeaderMap) -> Result<Response, HttpError> {
         let user = self
             .db_client
             .get_user(Some(id), None, None)
             .await
             .map_err(|_| HttpError::server_error("failed to get user"))?
+            .filter(|user| user.status != UserStatus::Deactivated)
             .ok_or_else(|| HttpError::not_found(format!("user with id: {id} not found")))?;
 
+        let user_etag = etag(user.updated_at);
+
+        if if_none_match(headers, &user_etag) == Some(true) {
+            return Ok((StatusCode::NOT_MODIFIED, etag_header(&user_etag)).into_response());
+        }
+
         let user_response = UserResponseDto::from_user(&user);
 
         let body = Json(json!({
             "user": user_response,
         }));
 
-        Ok((StatusCode::OK, body).into_response())
+        Ok((StatusCode::OK, etag_header(&user_etag), body).into_response())
     }
 
-    pub async fn get_users(&self, page: usize, limit: usize) -> Result<Response, HttpError> {
-        let users = self
+    pub async fn get_users(
+        &self,
+        filter: UserFilter,
+        params: ListParams,
+    ) -> Result<Response, HttpError> {
+        let user_count = self
             .db_client
-            .get_users(page, limit)
+            .get_user_count(&filter)
             .await
-            .map_err(|_| HttpError::server_error("failed to get users"))?;
+            .map_err(|_| HttpError::server_error("failed to get user count"))?;
 
-        let user_count = self
+        let users = self
             .db_client
-            .get_user_count()
+            .get_users(&filter, &params)
             .await
-            .map_err(|_| HttpError::server_error("failed to get user count"))?;
+            .map_err(|_| HttpError::server_error("failed to get users"))?;
 
-        let users_response = UsersResponseDtoList::from_users(&users);
+        let users = params.to_page(users, user_count);
+        let users_response = UsersResponseDtoList::from_users(&users.items);
 
         let body = Json(json!({
             "users": users_response.users_list,
             "total": user_count,
-            "page": page,
-            "limit": limit,
+            "page": params.cursor.is_none().then_some(params.page),
+            "limit": params.limit,
+            "sort": params.sort.key(),
+            "next_cursor": users.next_cursor,
+            "prev_cursor": users.prev_cursor,
         }));
 
         Ok((StatusCode::OK, body).into_response())
     }
 
-    pub async fn update_user(&self, id: Uuid, data: UpdateUserDto) -> Result<Response, HttpError> {
+    pub async fn update_user(
+        &self,
+        id: Uuid,
+        data: UpdateUserDto,
+        headers: &HeaderMap,
+    ) -> Result<Response, HttpError> {
+        let user = self
+            .db_client
+            .get_user(Some(id), None, None)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get user"))?
+            .filter(|user| user.status != UserStatus::Deactivated)
+            .ok_or_else(|| HttpError::not_found(format!("user with id: {id} not found")))?;
+
+        let expected_updated_at = match if_match(headers, &etag(user.updated_at)) {
+            Some(false) => {
+                return Err(HttpError::precondition_failed(
+                    "user has been modified since it was fetched",
+                ));
+            }
+            Some(true) => Some(user.updated_at),
+            None => None,
+        };
+
         let password = match data.password {
             Some(password) => Some(
                 PasswordArgon::hash(&password)
@@ -74,12 +127,19 @@ impl UsersService {
             None => None,
         };
 
-        self.db_client
-            .update_user(id, data.email, password)
+        let user = self
+            .db_client
+            .update_user(id, data.email, password, expected_updated_at)
             .await
-            .map_err(|_| HttpError::server_error("failed to update user"))?;
+            .map_err(|_| HttpError::server_error("failed to update user"))?
+            .ok_or_else(|| match expected_updated_at {
+                Some(_) => {
+                    HttpError::precondition_failed("user has been modified since it was fetched")
+                }
+                None => HttpError::not_found(format!("user with id: {id} not found")),
+            })?;
 
-        Ok((StatusCode::NO_CONTENT).into_response())
+        Ok((StatusCode::NO_CONTENT, etag_header(&etag(user.updated_at))).into_response())
     }
 
     pub async fn update_user_role(
@@ -87,20 +147,170 @@ impl UsersService {
         id: Uuid,
         data: UpdateUserRoleDto,
     ) -> Result<Response, HttpError> {
+        self.ensure_role_exists(&data.role).await?;
+
         self.db_client
-            .update_user_role(id, data.role)
+            .update_user_role(id, &data.role)
             .await
             .map_err(|_| HttpError::server_error("failed to update user role"))?;
 
         Ok((StatusCode::NO_CONTENT).into_response())
     }
 
-    pub async fn delete_user(&self, id: Uuid) -> Result<Response, HttpError> {
+    // the account is purged once the grace period is over, signing in before that restores it
+    pub async fn delete_user(&self, actor_id: Uuid, id: Uuid) -> Result<Response, HttpError> {
+        self.db_client
+            .deactivate_user(id, actor_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to delete user"))?
+            .ok_or_else(|| HttpError::not_found(format!("user with id: {id} not found")))?;
+
+        self.db_client
+            .revoke_user_sessions(id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke user sessions"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn suspend_user(
+        &self,
+        actor_id: Uuid,
+        id: Uuid,
+        data: SuspendUserDto,
+    ) -> Result<Response, HttpError> {
+        if actor_id == id {
+            return Err(HttpError::bad_request(
+                "you cannot suspend your own account",
+            ));
+        }
+
+        if data.until.is_some_and(|until| until <= Utc::now()) {
+            return Err(HttpError::bad_request("until must be in the future"));
+        }
+
+        self.db_client
+            .suspend_user(id, data.reason, data.until)
+            .await
+            .map_err(|_| HttpError::server_error("failed to suspend user"))?
+            .ok_or_else(|| HttpError::not_found(format!("user with id: {id} not found")))?;
+
+        self.db_client
+            .revoke_user_sessions(id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke user sessions"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn unsuspend_user(&self, id: Uuid) -> Result<Response, HttpError> {
+        self.db_client
+            .unsuspend_user(id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to unsuspend user"))?
+            .ok_or_else(|| {
+                HttpError::not_found(format!("suspended user with id: {id} not found"))
+            })?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn revoke_user_sessions(&self, id: Uuid) -> Result<Response, HttpError> {
+        self.db_client
+            .revoke_user_sessions(id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to revoke user sessions"))?;
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    pub async fn update_mfa_policy(&self, data: UpdateMfaPolicyDto) -> Result<Response, HttpError> {
+        self.ensure_role_exists(&data.role).await?;
+
         self.db_client
-            .delete_user(id)
+            .set_mfa_required(&data.role, data.required)
             .await
-            .map_err(|_| HttpError::server_error("failed to delete user"))?;
+            .map_err(|_| HttpError::server_error("failed to update mfa policy"))?;
 
         Ok((StatusCode::NO_CONTENT).into_response())
     }
+
+    pub async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        data: CreateApiKeyDto,
+    ) -> Result<Response, HttpError> {
+        if data
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(HttpError::bad_request("expires_at must be in the future"));
+        }
+
+        let key = generate_api_key();
+        let mut scopes: Vec<String> = data.scopes.iter().map(|scope| scope.to_string()).collect();
+        scopes.sort();
+        scopes.dedup();
+
+        let api_key = self
+            .db_client
+            .create_api_key(
+                user_id,
+                data.name,
+                key.chars().take(12).collect(),
+                hash_token(&key),
+                scopes,
+                data.expires_at,
+            )
+            .await
+            .map_err(|_| HttpError::server_error("failed to create api key"))?;
+
+        let body = Json(json!({
+            "message": "Store the key now, it will not be shown again",
+            "key": key,
+            "api_key": api_key,
+        }));
+
+        Ok((StatusCode::CREATED, body).into_response())
+    }
+
+    pub async fn get_api_keys(&self, user_id: Uuid) -> Result<Response, HttpError> {
+        let api_keys = self
+            .db_client
+            .get_api_keys(user_id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get api keys"))?;
+
+        let body = Json(json!({
+            "api_keys": api_keys,
+        }));
+
+        Ok((StatusCode::OK, body).into_response())
+    }
+
+    pub async fn delete_api_key(&self, user_id: Uuid, id: Uuid) -> Result<Response, HttpError> {
+        let deleted = self
+            .db_client
+            .delete_api_key(user_id, id)
+            .await
+            .map_err(|_| HttpError::server_error("failed to delete api key"))?;
+
+        if !deleted {
+            return Err(HttpError::not_found(format!(
+                "api key with id: {id} not found"
+            )));
+        }
+
+        Ok((StatusCode::NO_CONTENT).into_response())
+    }
+
+    async fn ensure_role_exists(&self, role: &str) -> Result<(), HttpError> {
+        self.db_client
+            .get_role(role)
+            .await
+            .map_err(|_| HttpError::server_error("failed to get role"))?
+            .ok_or_else(|| HttpError::bad_request(format!("role `{role}` does not exist")))?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{Extensions, HeaderMap, header, request::Parts},
+};
+
+use crate::AppState;
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let peer_ip = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        let trusted_proxies = extensions
+            .get::<AppState>()
+            .map(|app_state| app_state.env.trusted_proxies.as_slice())
+            .unwrap_or_default();
+
+        let ip_address = match peer_ip {
+            Some(peer_ip) if trusted_proxies.contains(&peer_ip) => {
+                forwarded_ip(headers, trusted_proxies).unwrap_or(peer_ip)
+            }
+            Some(peer_ip) => peer_ip,
+            None => {
+                return Self {
+                    user_agent,
+                    ip_address: None,
+                };
+            }
+        };
+
+        Self {
+            user_agent,
+            ip_address: Some(ip_address.to_string()),
+        }
+    }
+}
+
+// the client is the last hop that is not one of our own proxies
+fn forwarded_ip(headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    if let Some(forwarded_for) = header("x-forwarded-for") {
+        return forwarded_for
+            .rsplit(',')
+            .filter_map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .find(|hop| !trusted_proxies.contains(hop));
+    }
+
+    header("x-real-ip").and_then(|value| value.trim().parse().ok())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::new(&parts.headers, &parts.extensions))
+    }
+}
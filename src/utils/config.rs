@@ -1,6 +1,7 @@
 use std::{
     env::var,
     fmt::{Display, Formatter, Result},
+    net::IpAddr,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -9,6 +10,10 @@ pub enum Routes {
     Auth,
     Users,
     Posts,
+    Comments,
+    Roles,
+    Tags,
+    WellKnown,
 }
 
 impl Display for Routes {
@@ -18,6 +23,134 @@ impl Display for Routes {
             Self::Auth => write!(f, "/auth"),
             Self::Users => write!(f, "/users"),
             Self::Posts => write!(f, "/posts"),
+            Self::Comments => write!(f, "/posts/{{id}}/comments"),
+            Self::Roles => write!(f, "/roles"),
+            Self::Tags => write!(f, "/tags"),
+            Self::WellKnown => write!(f, "/.well-known"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MailTransport {
+    Smtp,
+    File,
+    Memory,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoginAttemptStoreKind {
+    Memory,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitPolicy {
+    pub capacity: u32,
+    pub period_sec: u64,
+}
+
+impl RateLimitPolicy {
+    fn from_var(name: &str, default: &str) -> Self {
+        let value = var(name).unwrap_or_else(|_| default.to_string());
+
+        let (capacity, period_sec) = value
+            .split_once('/')
+            .unwrap_or_else(|| panic!("{name} must be formatted as <requests>/<seconds>"));
+
+        let capacity = capacity
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} requests must be a valid u32"));
+
+        let period_sec = period_sec
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} seconds must be a valid u64"));
+
+        Self {
+            capacity,
+            period_sec,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OAuthProviderKind {
+    Oidc,
+    Github,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthProvider {
+    pub name: String,
+    pub kind: OAuthProviderKind,
+    pub client_id: String,
+    pub client_secret: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    pub scopes: String,
+}
+
+impl OAuthProvider {
+    fn from_var(name: &str) -> Self {
+        let prefix = format!("OAUTH_{}", name.to_uppercase().replace('-', "_"));
+        let setting = |key: &str| var(format!("{prefix}_{key}")).ok();
+
+        let (kind, auth_url, token_url, userinfo_url, scopes) = match name {
+            "google" => (
+                OAuthProviderKind::Oidc,
+                Some("https://accounts.google.com/o/oauth2/v2/auth"),
+                Some("https://oauth2.googleapis.com/token"),
+                Some("https://openidconnect.googleapis.com/v1/userinfo"),
+                "openid email profile",
+            ),
+            "github" => (
+                OAuthProviderKind::Github,
+                Some("https://github.com/login/oauth/authorize"),
+                Some("https://github.com/login/oauth/access_token"),
+                Some("https://api.github.com/user"),
+                "read:user user:email",
+            ),
+            _ => (
+                OAuthProviderKind::Oidc,
+                None,
+                None,
+                None,
+                "openid email profile",
+            ),
+        };
+
+        let kind = match setting("KIND").as_deref() {
+            Some("oidc") => OAuthProviderKind::Oidc,
+            Some("github") => OAuthProviderKind::Github,
+            Some(_) => panic!("{prefix}_KIND must be one of: oidc, github"),
+            None => kind,
+        };
+
+        let url = |key: &str, default: Option<&str>| {
+            setting(key)
+                .or_else(|| default.map(String::from))
+                .unwrap_or_else(|| panic!("{prefix}_{key} must be set"))
+        };
+
+        Self {
+            name: name.to_string(),
+            kind,
+            client_id: url("CLIENT_ID", None),
+            client_secret: url("CLIENT_SECRET", None),
+            auth_url: url("AUTH_URL", auth_url),
+            token_url: url("TOKEN_URL", token_url),
+            userinfo_url: url("USERINFO_URL", userinfo_url),
+            scopes: setting("SCOPES").unwrap_or_else(|| scopes.to_string()),
         }
     }
 }
@@ -25,21 +158,82 @@ impl Display for Routes {
 #[derive(Debug, Clone)]
 pub struct Env {
     pub database_url: String,
-    pub jwt_access_token_secert: String,
+    pub jwt_algorithm: JwtAlgorithm,
+    pub jwt_access_token_secert: Option<String>,
+    pub jwt_private_key_file: Option<String>,
+    pub jwt_key_id: Option<String>,
+    pub jwt_public_key_files: Vec<String>,
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
     pub jwt_access_token_expires: i64,
     pub jwt_refresh_token_secert: String,
     pub jwt_refresh_token_expires: i64,
     pub port: u16,
     pub ip: String,
     pub local: String,
+    pub app_url: String,
+    pub mail_transport: MailTransport,
+    pub mail_from: String,
+    pub mail_dir: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_tls: bool,
+    pub email_verification_expires: i64,
+    pub password_reset_expires: i64,
+    pub password_reset_url: Option<String>,
+    pub mfa_pending_expires: i64,
+    pub login_attempts_store: LoginAttemptStoreKind,
+    pub login_max_attempts_per_email: i32,
+    pub login_max_attempts_per_ip: i32,
+    pub login_lockout_base: i64,
+    pub login_lockout_max: i64,
+    pub login_attempt_window: i64,
+    pub trusted_proxies: Vec<IpAddr>,
+    pub rate_limit_auth: RateLimitPolicy,
+    pub rate_limit_users: RateLimitPolicy,
+    pub rate_limit_posts: RateLimitPolicy,
+    pub rate_limit_posts_read: RateLimitPolicy,
+    pub oauth_providers: Vec<OAuthProvider>,
+    pub oauth_state_expires: i64,
+    pub account_deletion_grace_days: i64,
+    pub post_trash_retention_days: i64,
+    pub purge_interval: u64,
+    pub publish_interval: u64,
+    pub post_reaction_kinds: Vec<String>,
 }
 
 impl Env {
     pub fn init() -> Self {
         let database_url = var("DATABASE_URL").expect("DATABASE_URL must be set");
 
-        let jwt_access_token_secert =
-            var("JWT_ACCESS_TOKEN_SECRET").expect("JWT_ACCESS_TOKEN_SECRET must be set");
+        let jwt_algorithm = match var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string())
+            .as_str()
+        {
+            "HS256" => JwtAlgorithm::HS256,
+            "RS256" => JwtAlgorithm::RS256,
+            "EdDSA" => JwtAlgorithm::EdDSA,
+            _ => panic!("JWT_ALGORITHM must be one of: HS256, RS256, EdDSA"),
+        };
+
+        let jwt_access_token_secert = var("JWT_ACCESS_TOKEN_SECRET").ok();
+
+        let jwt_private_key_file = var("JWT_PRIVATE_KEY_FILE").ok();
+
+        let jwt_key_id = var("JWT_KEY_ID").ok();
+
+        let jwt_public_key_files = var("JWT_PUBLIC_KEY_FILES")
+            .map(|files| {
+                files
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|file| !file.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
 
         let jwt_access_token_expires = var("JWT_ACCESS_TOKEN_EXPIRES")
             .expect("JWT_ACCESS_TOKEN_EXPIRES must be set")
@@ -63,17 +257,213 @@ impl Env {
 
         let local = var("LOCAL").unwrap_or_else(|_| "localhost".to_string());
 
+        let app_url = var("APP_URL").unwrap_or_else(|_| format!("http://{local}:{port}"));
+
+        let jwt_issuer = var("JWT_ISSUER").unwrap_or_else(|_| app_url.clone());
+
+        let jwt_audience = var("JWT_AUDIENCE").unwrap_or_else(|_| "axum_posts".to_string());
+
+        let mail_transport = match var("MAIL_TRANSPORT")
+            .unwrap_or_else(|_| "file".to_string())
+            .as_str()
+        {
+            "smtp" => MailTransport::Smtp,
+            "file" => MailTransport::File,
+            "memory" => MailTransport::Memory,
+            _ => panic!("MAIL_TRANSPORT must be one of: smtp, file, memory"),
+        };
+
+        let mail_from =
+            var("MAIL_FROM").unwrap_or_else(|_| "Axum Posts <no-reply@localhost>".to_string());
+
+        let mail_dir = var("MAIL_DIR").unwrap_or_else(|_| "mails".to_string());
+
+        let smtp_host = var("SMTP_HOST").ok();
+
+        let smtp_port = var("SMTP_PORT")
+            .ok()
+            .map(|port| port.parse().expect("SMTP_PORT must be a valid u16"));
+
+        let smtp_username = var("SMTP_USERNAME").ok();
+
+        let smtp_password = var("SMTP_PASSWORD").ok();
+
+        let smtp_tls = var("SMTP_TLS")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()
+            .expect("SMTP_TLS must be a valid bool");
+
+        let email_verification_expires = var("EMAIL_VERIFICATION_EXPIRES")
+            .unwrap_or_else(|_| "86400".to_string())
+            .parse()
+            .expect("EMAIL_VERIFICATION_EXPIRES must be a valid i64");
+
+        let password_reset_expires = var("PASSWORD_RESET_EXPIRES")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("PASSWORD_RESET_EXPIRES must be a valid i64");
+
+        let password_reset_url = var("PASSWORD_RESET_URL").ok();
+
+        let mfa_pending_expires = var("MFA_PENDING_EXPIRES")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()
+            .expect("MFA_PENDING_EXPIRES must be a valid i64");
+
+        let login_attempts_store = match var("LOGIN_ATTEMPTS_STORE")
+            .unwrap_or_else(|_| "memory".to_string())
+            .as_str()
+        {
+            "memory" => LoginAttemptStoreKind::Memory,
+            "postgres" => LoginAttemptStoreKind::Postgres,
+            _ => panic!("LOGIN_ATTEMPTS_STORE must be one of: memory, postgres"),
+        };
+
+        let login_max_attempts_per_email = var("LOGIN_MAX_ATTEMPTS_PER_EMAIL")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .expect("LOGIN_MAX_ATTEMPTS_PER_EMAIL must be a valid i32");
+
+        let login_max_attempts_per_ip = var("LOGIN_MAX_ATTEMPTS_PER_IP")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()
+            .expect("LOGIN_MAX_ATTEMPTS_PER_IP must be a valid i32");
+
+        let login_lockout_base = var("LOGIN_LOCKOUT_BASE")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("LOGIN_LOCKOUT_BASE must be a valid i64");
+
+        let login_lockout_max = var("LOGIN_LOCKOUT_MAX")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("LOGIN_LOCKOUT_MAX must be a valid i64");
+
+        let login_attempt_window = var("LOGIN_ATTEMPT_WINDOW")
+            .unwrap_or_else(|_| "900".to_string())
+            .parse()
+            .expect("LOGIN_ATTEMPT_WINDOW must be a valid i64");
+
+        let trusted_proxies = var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                proxy
+                    .parse()
+                    .expect("TRUSTED_PROXIES must be a comma separated list of ip addresses")
+            })
+            .collect();
+
+        let rate_limit_auth = RateLimitPolicy::from_var("RATE_LIMIT_AUTH", "20/60");
+
+        let rate_limit_users = RateLimitPolicy::from_var("RATE_LIMIT_USERS", "60/60");
+
+        let rate_limit_posts = RateLimitPolicy::from_var("RATE_LIMIT_POSTS", "30/60");
+
+        let rate_limit_posts_read = RateLimitPolicy::from_var("RATE_LIMIT_POSTS_READ", "300/60");
+
+        let oauth_providers = var("OAUTH_PROVIDERS")
+            .map(|providers| {
+                providers
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|provider| !provider.is_empty())
+                    .map(|provider| OAuthProvider::from_var(&provider.to_lowercase()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let oauth_state_expires = var("OAUTH_STATE_EXPIRES")
+            .unwrap_or_else(|_| "600".to_string())
+            .parse()
+            .expect("OAUTH_STATE_EXPIRES must be a valid i64");
+
+        let account_deletion_grace_days = var("ACCOUNT_DELETION_GRACE_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("ACCOUNT_DELETION_GRACE_DAYS must be a valid i64");
+
+        let post_trash_retention_days = var("POST_TRASH_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .expect("POST_TRASH_RETENTION_DAYS must be a valid i64");
+
+        let purge_interval = var("PURGE_INTERVAL")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()
+            .expect("PURGE_INTERVAL must be a valid u64");
+
+        let publish_interval = var("PUBLISH_INTERVAL")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .expect("PUBLISH_INTERVAL must be a valid u64");
+
+        // `like` is always available, the rest can be configured
+        let mut post_reaction_kinds: Vec<String> = var("POST_REACTION_KINDS")
+            .unwrap_or_else(|_| "like,love,laugh,wow,sad".to_string())
+            .split(',')
+            .map(|kind| kind.trim().to_lowercase())
+            .filter(|kind| !kind.is_empty())
+            .collect();
+
+        if !post_reaction_kinds.iter().any(|kind| kind == "like") {
+            post_reaction_kinds.insert(0, "like".to_string());
+        }
+
+        if post_reaction_kinds.iter().any(|kind| kind.len() > 32) {
+            panic!("POST_REACTION_KINDS entries must be at most 32 characters");
+        }
+
         println!("Configuration loaded!");
 
         Self {
             database_url,
+            jwt_algorithm,
             jwt_access_token_secert,
+            jwt_private_key_file,
+            jwt_key_id,
+            jwt_public_key_files,
+            jwt_issuer,
+            jwt_audience,
             jwt_access_token_expires,
             jwt_refresh_token_secert,
             jwt_refresh_token_expires,
             port,
             ip,
             local,
+            app_url,
+            mail_transport,
+            mail_from,
+            mail_dir,
+            smtp_host,
+            smtp_port,
+            smtp_username,
+            smtp_password,
+            smtp_tls,
+            email_verification_expires,
+            password_reset_expires,
+            password_reset_url,
+            mfa_pending_expires,
+            login_attempts_store,
+            login_max_attempts_per_email,
+            login_max_attempts_per_ip,
+            login_lockout_base,
+            login_lockout_max,
+            login_attempt_window,
+            trusted_proxies,
+            rate_limit_auth,
+            rate_limit_users,
+            rate_limit_posts,
+            rate_limit_posts_read,
+            oauth_providers,
+            oauth_state_expires,
+            account_deletion_grace_days,
+            post_trash_retention_days,
+            purge_interval,
+            publish_interval,
+            post_reaction_kinds,
         }
     }
 }
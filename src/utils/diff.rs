@@ -0,0 +1,81 @@
+use similar::{ChangeTag, TextDiff};
+
+use crate::dtos::post_dto::LineChange;
+
+pub fn line_diff(old: &str, new: &str) -> Vec<LineChange> {
+    // a missing trailing newline would otherwise show the last line as changed
+    let old = format!("{}\n", old.trim_end_matches(['\r', '\n']));
+    let new = format!("{}\n", new.trim_end_matches(['\r', '\n']));
+
+    TextDiff::from_lines(&old, &new)
+        .iter_all_changes()
+        .map(|change| LineChange {
+            tag: match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Delete => "delete",
+                ChangeTag::Insert => "insert",
+            }
+            .to_string(),
+            old_line: change.old_index().map(|index| index + 1),
+            new_line: change.new_index().map(|index| index + 1),
+            value: change.value().trim_end_matches(['\r', '\n']).to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<(String, Option<usize>, Option<usize>, String)> {
+        line_diff(old, new)
+            .into_iter()
+            .map(|change| (change.tag, change.old_line, change.new_line, change.value))
+            .collect()
+    }
+
+    fn change(
+        tag: &str,
+        old_line: Option<usize>,
+        new_line: Option<usize>,
+        value: &str,
+    ) -> (String, Option<usize>, Option<usize>, String) {
+        (tag.to_string(), old_line, new_line, value.to_string())
+    }
+
+    #[test]
+    fn marks_replaced_lines_with_their_line_numbers() {
+        assert_eq!(
+            changes("one\ntwo\nthree", "one\nTWO\nthree\nfour"),
+            vec![
+                change("equal", Some(1), Some(1), "one"),
+                change("delete", Some(2), None, "two"),
+                change("insert", None, Some(2), "TWO"),
+                change("equal", Some(3), Some(3), "three"),
+                change("insert", None, Some(4), "four"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_trailing_newlines_and_line_endings() {
+        assert_eq!(
+            changes("one\r\ntwo", "one\r\ntwo\n\n"),
+            vec![
+                change("equal", Some(1), Some(1), "one"),
+                change("equal", Some(2), Some(2), "two"),
+            ]
+        );
+    }
+
+    #[test]
+    fn an_empty_side_is_a_single_blank_line() {
+        assert_eq!(
+            changes("", "hello"),
+            vec![
+                change("delete", Some(1), None, ""),
+                change("insert", None, Some(1), "hello"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use axum::http::{HeaderMap, HeaderValue, header};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+pub fn etag(updated_at: DateTime<Utc>) -> String {
+    format!("\"{:x}\"", updated_at.timestamp_micros())
+}
+
+// for representations that change without touching `updated_at`
+pub fn etag_of<T: Serialize>(value: &T) -> String {
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    format!("\"{}\"", hex::encode(&Sha256::digest(bytes)[..16]))
+}
+
+pub fn etag_header(etag: &str) -> [(header::HeaderName, HeaderValue); 1] {
+    [(
+        header::ETAG,
+        HeaderValue::from_str(etag).expect("etag is a valid header value"),
+    )]
+}
+
+// `None` when the header is missing, otherwise whether one of the listed tags matches
+pub fn if_match(headers: &HeaderMap, etag: &str) -> Option<bool> {
+    matches_header(headers, header::IF_MATCH, etag, false)
+}
+
+// If-None-Match uses the weak comparison, so `W/` tags match as well
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> Option<bool> {
+    matches_header(headers, header::IF_NONE_MATCH, etag, true)
+}
+
+fn matches_header(
+    headers: &HeaderMap,
+    name: header::HeaderName,
+    current: &str,
+    weak: bool,
+) -> Option<bool> {
+    let mut values = headers.get_all(name).iter().peekable();
+
+    values.peek()?;
+
+    Some(values.filter_map(|value| value.to_str().ok()).any(|value| {
+        value.split(',').map(str::trim).any(|tag| {
+            let tag = match weak {
+                true => tag.strip_prefix("W/").unwrap_or(tag),
+                false => tag,
+            };
+
+            tag == "*" || tag == current
+        })
+    }))
+}
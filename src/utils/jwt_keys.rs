@@ -0,0 +1,305 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    fs::read_to_string,
+    sync::Arc,
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    errors::{Error as JwtError, ErrorKind as JwtErrorKind},
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use rsa::{
+    RsaPrivateKey, RsaPublicKey,
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    traits::PublicKeyParts,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::utils::config::{Env, JwtAlgorithm};
+
+#[derive(Clone)]
+pub struct JwtKeys {
+    inner: Arc<KeySet>,
+}
+
+struct KeySet {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    encoding_key: EncodingKey,
+    decoding_keys: HashMap<Option<String>, (Algorithm, DecodingKey)>,
+    jwks: JwkSet,
+    issuer: String,
+    audience: String,
+}
+
+enum PublicKey {
+    Rsa(RsaPublicKey),
+    Ed(VerifyingKey),
+}
+
+impl PublicKey {
+    fn from_pem(pem: &str) -> Option<Self> {
+        RsaPublicKey::from_public_key_pem(pem)
+            .or_else(|_| RsaPublicKey::from_pkcs1_pem(pem))
+            .map(Self::Rsa)
+            .or_else(|_| VerifyingKey::from_public_key_pem(pem).map(Self::Ed))
+            .ok()
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        match self {
+            Self::Rsa(_) => Algorithm::RS256,
+            Self::Ed(_) => Algorithm::EdDSA,
+        }
+    }
+
+    // RFC 7638 thumbprint, used as the kid when none is configured
+    fn thumbprint(&self) -> String {
+        let members = match self {
+            Self::Rsa(key) => json!({
+                "e": URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                "kty": "RSA",
+                "n": URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+            }),
+            Self::Ed(key) => json!({
+                "crv": "Ed25519",
+                "kty": "OKP",
+                "x": URL_SAFE_NO_PAD.encode(key.to_bytes()),
+            }),
+        };
+
+        URL_SAFE_NO_PAD.encode(Sha256::digest(members.to_string().as_bytes()))
+    }
+
+    fn to_jwk(&self, kid: &str) -> Jwk {
+        let (key_algorithm, algorithm) = match self {
+            Self::Rsa(key) => (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            ),
+            Self::Ed(key) => (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(key.to_bytes()),
+                }),
+            ),
+        };
+
+        Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                key_algorithm: Some(key_algorithm),
+                key_id: Some(kid.to_string()),
+                ..Default::default()
+            },
+            algorithm,
+        }
+    }
+}
+
+impl JwtKeys {
+    pub fn from_secret(secret: &[u8], issuer: &str, audience: &str) -> Self {
+        let mut decoding_keys = HashMap::new();
+        decoding_keys.insert(None, (Algorithm::HS256, DecodingKey::from_secret(secret)));
+
+        Self {
+            inner: Arc::new(KeySet {
+                algorithm: Algorithm::HS256,
+                kid: None,
+                encoding_key: EncodingKey::from_secret(secret),
+                decoding_keys,
+                jwks: JwkSet { keys: vec![] },
+                issuer: issuer.to_string(),
+                audience: audience.to_string(),
+            }),
+        }
+    }
+
+    pub fn from_pem_files(
+        algorithm: JwtAlgorithm,
+        private_key_file: &str,
+        kid: Option<&str>,
+        public_key_files: &[String],
+        issuer: &str,
+        audience: &str,
+    ) -> Self {
+        let pem = read_to_string(private_key_file)
+            .unwrap_or_else(|e| panic!("failed to read {private_key_file}: {e}"));
+
+        let (algorithm, encoding_key, public_key) = match algorithm {
+            JwtAlgorithm::RS256 => {
+                let private_key = RsaPrivateKey::from_pkcs8_pem(&pem)
+                    .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+                    .unwrap_or_else(|_| panic!("{private_key_file} must be an RSA private key"));
+
+                (
+                    Algorithm::RS256,
+                    EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap_or_else(|_| {
+                        panic!("{private_key_file} must be an RSA private key")
+                    }),
+                    PublicKey::Rsa(private_key.to_public_key()),
+                )
+            }
+            JwtAlgorithm::EdDSA => {
+                let private_key = SigningKey::from_pkcs8_pem(&pem).unwrap_or_else(|_| {
+                    panic!("{private_key_file} must be an Ed25519 private key")
+                });
+
+                (
+                    Algorithm::EdDSA,
+                    EncodingKey::from_ed_pem(pem.as_bytes()).unwrap_or_else(|_| {
+                        panic!("{private_key_file} must be an Ed25519 private key")
+                    }),
+                    PublicKey::Ed(private_key.verifying_key()),
+                )
+            }
+            JwtAlgorithm::HS256 => panic!("HS256 keys are loaded from a secret, not a pem file"),
+        };
+
+        let kid = kid
+            .map(String::from)
+            .unwrap_or_else(|| public_key.thumbprint());
+
+        let mut public_keys = vec![(kid.clone(), public_key)];
+
+        // previous keys, as `path` or `kid=path`, stay valid for verification only
+        for entry in public_key_files {
+            let (kid, file) = match entry.split_once('=') {
+                Some((kid, file)) => (Some(kid.trim()), file.trim()),
+                None => (None, entry.as_str()),
+            };
+
+            let pem = read_to_string(file).unwrap_or_else(|e| panic!("failed to read {file}: {e}"));
+
+            let public_key = PublicKey::from_pem(&pem)
+                .unwrap_or_else(|| panic!("{file} must be an RSA or Ed25519 public key"));
+
+            let kid = kid
+                .map(String::from)
+                .unwrap_or_else(|| public_key.thumbprint());
+
+            public_keys.push((kid, public_key));
+        }
+
+        let mut decoding_keys = HashMap::new();
+        let mut jwks = JwkSet { keys: vec![] };
+
+        for (kid, public_key) in public_keys {
+            if decoding_keys.contains_key(&Some(kid.clone())) {
+                continue;
+            }
+
+            let jwk = public_key.to_jwk(&kid);
+            let decoding_key = DecodingKey::from_jwk(&jwk)
+                .unwrap_or_else(|_| panic!("failed to load the verification key {kid}"));
+
+            decoding_keys.insert(Some(kid), (public_key.algorithm(), decoding_key));
+            jwks.keys.push(jwk);
+        }
+
+        Self {
+            inner: Arc::new(KeySet {
+                algorithm,
+                kid: Some(kid),
+                encoding_key,
+                decoding_keys,
+                jwks,
+                issuer: issuer.to_string(),
+                audience: audience.to_string(),
+            }),
+        }
+    }
+
+    pub fn access_from_env(env: &Env) -> Self {
+        match env.jwt_algorithm {
+            JwtAlgorithm::HS256 => Self::from_secret(
+                env.jwt_access_token_secert
+                    .as_deref()
+                    .expect("JWT_ACCESS_TOKEN_SECRET must be set when JWT_ALGORITHM is HS256")
+                    .as_bytes(),
+                &env.jwt_issuer,
+                &env.jwt_audience,
+            ),
+            algorithm => Self::from_pem_files(
+                algorithm,
+                env.jwt_private_key_file.as_deref().expect(
+                    "JWT_PRIVATE_KEY_FILE must be set when JWT_ALGORITHM is RS256 or EdDSA",
+                ),
+                env.jwt_key_id.as_deref(),
+                &env.jwt_public_key_files,
+                &env.jwt_issuer,
+                &env.jwt_audience,
+            ),
+        }
+    }
+
+    pub fn refresh_from_env(env: &Env) -> Self {
+        Self::from_secret(
+            env.jwt_refresh_token_secert.as_bytes(),
+            &env.jwt_issuer,
+            &env.jwt_audience,
+        )
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.inner.issuer
+    }
+
+    pub fn audience(&self) -> &str {
+        &self.inner.audience
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let mut header = Header::new(self.inner.algorithm);
+        header.kid = self.inner.kid.clone();
+
+        encode(&header, claims, &self.inner.encoding_key)
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, JwtError> {
+        let header = decode_header(token)?;
+
+        let (algorithm, decoding_key) = self
+            .inner
+            .decoding_keys
+            .get(&header.kid)
+            .ok_or(JwtErrorKind::InvalidToken)?;
+
+        let mut validation = Validation::new(*algorithm);
+        validation.set_issuer(&[&self.inner.issuer]);
+        validation.set_audience(&[&self.inner.audience]);
+        validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+        validation.validate_nbf = true;
+
+        decode::<T>(token, decoding_key, &validation).map(|token_data| token_data.claims)
+    }
+
+    pub fn jwks(&self) -> &JwkSet {
+        &self.inner.jwks
+    }
+}
+
+impl Debug for JwtKeys {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("JwtKeys")
+            .field("algorithm", &self.inner.algorithm)
+            .field("kid", &self.inner.kid)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use axum::http::header;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::{
+    db::DBClient,
+    dtos::auth_dto::LoginAttempt,
+    error::{ErrorMessage, HttpError},
+    utils::config::{Env, LoginAttemptStoreKind},
+};
+
+#[async_trait]
+pub trait LoginAttemptStore: Debug + Send + Sync {
+    async fn get_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, ErrorMessage>;
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        window_sec: i64,
+    ) -> Result<LoginAttempt, ErrorMessage>;
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), ErrorMessage>;
+
+    async fn clear_attempts(&self, key: &str) -> Result<(), ErrorMessage>;
+}
+
+pub fn login_attempt_store_from_env(env: &Env, db_client: &DBClient) -> Arc<dyn LoginAttemptStore> {
+    match env.login_attempts_store {
+        LoginAttemptStoreKind::Memory => Arc::new(MemoryLoginAttemptStore::new()),
+        LoginAttemptStoreKind::Postgres => Arc::new(db_client.clone()),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoginAttemptStore {
+    attempts: Arc<Mutex<HashMap<String, LoginAttempt>>>,
+}
+
+impl MemoryLoginAttemptStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for MemoryLoginAttemptStore {
+    async fn get_attempt(&self, key: &str) -> Result<Option<LoginAttempt>, ErrorMessage> {
+        let attempts = self
+            .attempts
+            .lock()
+            .map_err(|_| ErrorMessage::ServerError)?;
+
+        Ok(attempts.get(key).cloned())
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        window_sec: i64,
+    ) -> Result<LoginAttempt, ErrorMessage> {
+        let mut attempts = self
+            .attempts
+            .lock()
+            .map_err(|_| ErrorMessage::ServerError)?;
+
+        let now = Utc::now();
+        let window_start = now - TimeDelta::seconds(window_sec);
+
+        attempts.retain(|_, attempt| {
+            attempt.last_failure_at >= window_start
+                || attempt.locked_until.is_some_and(|until| until > now)
+        });
+
+        let attempt = attempts
+            .entry(key.to_string())
+            .or_insert_with(|| LoginAttempt {
+                key: key.to_string(),
+                failures: 0,
+                locked_until: None,
+                last_failure_at: now,
+            });
+
+        if attempt.last_failure_at < window_start {
+            attempt.failures = 0;
+        }
+
+        attempt.failures += 1;
+        attempt.last_failure_at = now;
+
+        Ok(attempt.clone())
+    }
+
+    async fn lock(&self, key: &str, until: DateTime<Utc>) -> Result<(), ErrorMessage> {
+        let mut attempts = self
+            .attempts
+            .lock()
+            .map_err(|_| ErrorMessage::ServerError)?;
+
+        if let Some(attempt) = attempts.get_mut(key) {
+            attempt.locked_until = Some(until);
+        }
+
+        Ok(())
+    }
+
+    async fn clear_attempts(&self, key: &str) -> Result<(), ErrorMessage> {
+        let mut attempts = self
+            .attempts
+            .lock()
+            .map_err(|_| ErrorMessage::ServerError)?;
+        attempts.remove(key);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginThrottle {
+    store: Arc<dyn LoginAttemptStore>,
+    max_attempts_per_email: i32,
+    max_attempts_per_ip: i32,
+    lockout_base: i64,
+    lockout_max: i64,
+    attempt_window: i64,
+}
+
+impl LoginThrottle {
+    pub fn new(store: Arc<dyn LoginAttemptStore>, env: &Env) -> Self {
+        Self {
+            store,
+            max_attempts_per_email: env.login_max_attempts_per_email,
+            max_attempts_per_ip: env.login_max_attempts_per_ip,
+            lockout_base: env.login_lockout_base,
+            lockout_max: env.login_lockout_max,
+            attempt_window: env.login_attempt_window,
+        }
+    }
+
+    pub fn email_key(email: &str) -> String {
+        format!("email:{}", email.trim().to_lowercase())
+    }
+
+    pub fn ip_key(ip: &str) -> String {
+        format!("ip:{ip}")
+    }
+
+    pub async fn check(&self, email_key: &str, ip_key: Option<&str>) -> Result<(), HttpError> {
+        let now = Utc::now();
+
+        for key in [Some(email_key), ip_key].into_iter().flatten() {
+            let attempt = self
+                .store
+                .get_attempt(key)
+                .await
+                .map_err(|_| HttpError::server_error("failed to check login attempts"))?;
+
+            if let Some(locked_until) = attempt.and_then(|attempt| attempt.locked_until)
+                && locked_until > now
+            {
+                let retry_after = ((locked_until - now).num_milliseconds() + 999) / 1000;
+
+                return Err(HttpError::too_many_requests(format!(
+                    "too many failed login attempts, try again in {retry_after} seconds"
+                ))
+                .with_header(header::RETRY_AFTER, retry_after));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn record_failure(
+        &self,
+        email_key: &str,
+        ip_key: Option<&str>,
+    ) -> Result<(), HttpError> {
+        self.record_key_failure(email_key, self.max_attempts_per_email)
+            .await?;
+
+        if let Some(ip_key) = ip_key {
+            self.record_key_failure(ip_key, self.max_attempts_per_ip)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn clear(&self, key: &str) -> Result<(), HttpError> {
+        self.store
+            .clear_attempts(key)
+            .await
+            .map_err(|_| HttpError::server_error("failed to clear login attempts"))
+    }
+
+    async fn record_key_failure(&self, key: &str, max_attempts: i32) -> Result<(), HttpError> {
+        let attempt = self
+            .store
+            .record_failure(key, self.attempt_window)
+            .await
+            .map_err(|_| HttpError::server_error("failed to record login attempt"))?;
+
+        if attempt.failures < max_attempts {
+            return Ok(());
+        }
+
+        let exponent = (attempt.failures - max_attempts).min(30) as u32;
+        let lockout = self
+            .lockout_base
+            .saturating_mul(2_i64.saturating_pow(exponent))
+            .min(self.lockout_max);
+
+        self.store
+            .lock(key, Utc::now() + TimeDelta::seconds(lockout))
+            .await
+            .map_err(|_| HttpError::server_error("failed to lock login attempts"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle {
+            store: Arc::new(MemoryLoginAttemptStore::new()),
+            max_attempts_per_email: 3,
+            max_attempts_per_ip: 5,
+            lockout_base: 60,
+            lockout_max: 300,
+            attempt_window: 900,
+        }
+    }
+
+    async fn locked_for(throttle: &LoginThrottle, key: &str) -> i64 {
+        let attempt = throttle.store.get_attempt(key).await.unwrap().unwrap();
+        (attempt.locked_until.unwrap() - Utc::now()).num_seconds()
+    }
+
+    #[test]
+    fn email_key_ignores_case_and_surrounding_spaces() {
+        assert_eq!(
+            LoginThrottle::email_key("  Ann@Example.com "),
+            "email:ann@example.com"
+        );
+        assert_eq!(LoginThrottle::ip_key("10.0.0.1"), "ip:10.0.0.1");
+    }
+
+    #[tokio::test]
+    async fn locks_an_email_after_max_attempts() {
+        let throttle = throttle();
+        let email = LoginThrottle::email_key("ann@example.com");
+
+        for _ in 0..2 {
+            throttle.record_failure(&email, None).await.unwrap();
+        }
+        assert!(throttle.check(&email, None).await.is_ok());
+
+        throttle.record_failure(&email, None).await.unwrap();
+
+        let err = throttle.check(&email, None).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(
+            err.headers
+                .iter()
+                .any(|(name, _)| *name == header::RETRY_AFTER)
+        );
+    }
+
+    #[tokio::test]
+    async fn lockout_doubles_up_to_the_maximum() {
+        let throttle = throttle();
+        let email = LoginThrottle::email_key("ann@example.com");
+
+        for _ in 0..3 {
+            throttle.record_failure(&email, None).await.unwrap();
+        }
+        assert!((58..=60).contains(&locked_for(&throttle, &email).await));
+
+        throttle.record_failure(&email, None).await.unwrap();
+        assert!((118..=120).contains(&locked_for(&throttle, &email).await));
+
+        for _ in 0..5 {
+            throttle.record_failure(&email, None).await.unwrap();
+        }
+        assert!((298..=300).contains(&locked_for(&throttle, &email).await));
+    }
+
+    #[tokio::test]
+    async fn ip_lock_applies_to_every_email() {
+        let throttle = throttle();
+        let ip = LoginThrottle::ip_key("10.0.0.1");
+
+        for n in 0..5 {
+            let email = LoginThrottle::email_key(&format!("user{n}@example.com"));
+            throttle.record_failure(&email, Some(&ip)).await.unwrap();
+        }
+
+        let other = LoginThrottle::email_key("ann@example.com");
+        assert!(throttle.check(&other, None).await.is_ok());
+        assert!(throttle.check(&other, Some(&ip)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn clear_lifts_the_lock() {
+        let throttle = throttle();
+        let email = LoginThrottle::email_key("ann@example.com");
+
+        for _ in 0..3 {
+            throttle.record_failure(&email, None).await.unwrap();
+        }
+        throttle.clear(&email).await.unwrap();
+
+        assert!(throttle.check(&email, None).await.is_ok());
+        assert!(throttle.store.get_attempt(&email).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn memory_store_restarts_the_count_after_the_window() {
+        let store = MemoryLoginAttemptStore::new();
+
+        store.record_failure("email:ann", 900).await.unwrap();
+        store
+            .attempts
+            .lock()
+            .unwrap()
+            .get_mut("email:ann")
+            .unwrap()
+            .last_failure_at = Utc::now() - TimeDelta::seconds(901);
+
+        let attempt = store.record_failure("email:ann", 900).await.unwrap();
+        assert_eq!(attempt.failures, 1);
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    fmt::Debug,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::ErrorMessage,
+    utils::config::{Env, MailTransport},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Debug + Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), ErrorMessage>;
+}
+
+pub fn mailer_from_env(env: &Env) -> Arc<dyn Mailer> {
+    match env.mail_transport {
+        MailTransport::Smtp => Arc::new(SmtpMailer::from_env(env)),
+        MailTransport::File => Arc::new(FileMailer::new(&env.mail_dir)),
+        MailTransport::Memory => Arc::new(MemoryMailer::new()),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env(env: &Env) -> Self {
+        let host = env
+            .smtp_host
+            .as_deref()
+            .expect("SMTP_HOST must be set when MAIL_TRANSPORT is smtp");
+
+        let mut builder = if env.smtp_tls {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host).expect("SMTP_HOST must be valid")
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        };
+
+        if let Some(port) = env.smtp_port {
+            builder = builder.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (&env.smtp_username, &env.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let from = env
+            .mail_from
+            .parse()
+            .expect("MAIL_FROM must be a valid mailbox");
+
+        Self {
+            transport: builder.build(),
+            from,
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), ErrorMessage> {
+        let to: Mailbox = mail
+            .to
+            .parse()
+            .map_err(|_| ErrorMessage::MailDeliveryFailed)?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body)
+            .map_err(|_| ErrorMessage::MailDeliveryFailed)?;
+
+        self.transport.send(message).await.map_err(|e| {
+            eprintln!("Failed to send mail: {e}");
+            ErrorMessage::MailDeliveryFailed
+        })?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), ErrorMessage> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|_| ErrorMessage::MailDeliveryFailed)?;
+
+        let file_name = format!("{}-{}.eml", Utc::now().timestamp_millis(), Uuid::new_v4());
+        let content = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            mail.to, mail.subject, mail.body
+        );
+
+        tokio::fs::write(self.dir.join(file_name), content)
+            .await
+            .map_err(|_| ErrorMessage::MailDeliveryFailed)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMailer {
+    outbox: Arc<Mutex<Vec<Mail>>>,
+}
+
+impl MemoryMailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn outbox(&self) -> Vec<Mail> {
+        self.outbox
+            .lock()
+            .map(|outbox| outbox.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, mail: Mail) -> Result<(), ErrorMessage> {
+        self.outbox
+            .lock()
+            .map_err(|_| ErrorMessage::MailDeliveryFailed)?
+            .push(mail);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail() -> Mail {
+        Mail {
+            to: "ann@example.com".to_string(),
+            subject: "Hello".to_string(),
+            body: "Hi Ann".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_mailer_keeps_sent_mails_in_order() {
+        let mailer = MemoryMailer::new();
+
+        mailer.send(mail()).await.unwrap();
+        mailer
+            .send(Mail {
+                subject: "Again".to_string(),
+                ..mail()
+            })
+            .await
+            .unwrap();
+
+        let outbox = mailer.outbox();
+        assert_eq!(outbox.len(), 2);
+        assert_eq!(outbox[0], mail());
+        assert_eq!(outbox[1].subject, "Again");
+    }
+
+    #[tokio::test]
+    async fn file_mailer_writes_one_file_per_mail() {
+        let dir = std::env::temp_dir().join(format!("axum-posts-mails-{}", Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir);
+
+        mailer.send(mail()).await.unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let content = std::fs::read_to_string(entries.next().unwrap().unwrap().path()).unwrap();
+        assert!(entries.next().is_none());
+        assert_eq!(content, "To: ann@example.com\nSubject: Hello\n\nHi Ann\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
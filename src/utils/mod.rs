@@ -1,6 +1,17 @@
+pub mod client_info;
 pub mod config;
+pub mod diff;
+pub mod etag;
+pub mod jwt_keys;
+pub mod login_throttle;
+pub mod mailer;
+pub mod oauth;
+pub mod pagination;
 pub mod password;
+pub mod publish;
+pub mod purge;
 pub mod token;
+pub mod totp;
 
 pub fn print_running(local: &str, port: u16, ip: &str) {
     println!("    🚀 Backend server is running (Axum Rust)");
@@ -0,0 +1,401 @@
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{DecodingKey, Validation, decode};
+use reqwest::{Client, Url, header};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    dtos::oauth_dto::OAuthProfile,
+    error::ErrorMessage,
+    utils::config::{
+        Env, OAuthProvider, OAuthProviderKind,
+        Routes::{Auth, Base},
+    },
+};
+
+#[derive(Debug, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubUser {
+    id: u64,
+    login: String,
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    http: Client,
+    providers: Arc<Vec<OAuthProvider>>,
+    app_url: String,
+}
+
+impl OAuthClient {
+    pub fn new(env: &Env) -> Self {
+        let http = Client::builder()
+            .user_agent("axum_posts")
+            .build()
+            .expect("failed to build the oauth http client");
+
+        Self {
+            http,
+            providers: Arc::new(env.oauth_providers.clone()),
+            app_url: env.app_url.clone(),
+        }
+    }
+
+    pub fn provider(&self, name: &str) -> Option<&OAuthProvider> {
+        self.providers.iter().find(|provider| provider.name == name)
+    }
+
+    pub fn redirect_uri(&self, provider: &OAuthProvider) -> String {
+        format!(
+            "{}{Base}{Auth}/oauth/{}/callback",
+            self.app_url.trim_end_matches('/'),
+            provider.name
+        )
+    }
+
+    pub fn authorize_url(
+        &self,
+        provider: &OAuthProvider,
+        state: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<String, ErrorMessage> {
+        let mut url = Url::parse_with_params(
+            &provider.auth_url,
+            &[
+                ("response_type", "code"),
+                ("client_id", provider.client_id.as_str()),
+                ("redirect_uri", self.redirect_uri(provider).as_str()),
+                ("scope", provider.scopes.as_str()),
+                ("state", state),
+                ("code_challenge", code_challenge(code_verifier).as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| ErrorMessage::OAuthProviderError)?;
+
+        if provider.kind == OAuthProviderKind::Oidc {
+            url.query_pairs_mut().append_pair("nonce", nonce);
+        }
+
+        Ok(url.to_string())
+    }
+
+    pub async fn exchange_code(
+        &self,
+        provider: &OAuthProvider,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuthTokens, ErrorMessage> {
+        self.http
+            .post(&provider.token_url)
+            .header(header::ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri(provider).as_str()),
+                ("client_id", provider.client_id.as_str()),
+                ("client_secret", provider.client_secret.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|_| ErrorMessage::OAuthProviderError)?
+            .json::<OAuthTokens>()
+            .await
+            .map_err(|_| ErrorMessage::OAuthProviderError)
+    }
+
+    pub async fn fetch_profile(
+        &self,
+        provider: &OAuthProvider,
+        tokens: &OAuthTokens,
+        nonce: &str,
+    ) -> Result<OAuthProfile, ErrorMessage> {
+        match provider.kind {
+            OAuthProviderKind::Oidc => self.fetch_oidc_profile(provider, tokens, nonce).await,
+            OAuthProviderKind::Github => {
+                self.fetch_github_profile(provider, &tokens.access_token)
+                    .await
+            }
+        }
+    }
+
+    async fn fetch_oidc_profile(
+        &self,
+        provider: &OAuthProvider,
+        tokens: &OAuthTokens,
+        nonce: &str,
+    ) -> Result<OAuthProfile, ErrorMessage> {
+        let id_token = tokens
+            .id_token
+            .as_deref()
+            .ok_or(ErrorMessage::InvalidIdToken)?;
+        let id_claims = verify_id_token(provider, id_token, nonce)?;
+
+        let claims: Value = self
+            .get_json(&provider.userinfo_url, &tokens.access_token)
+            .await?;
+
+        let subject = claims["sub"]
+            .as_str()
+            .ok_or(ErrorMessage::OAuthProviderError)?
+            .to_string();
+
+        if subject != id_claims.sub {
+            return Err(ErrorMessage::InvalidIdToken);
+        }
+
+        // some providers send email_verified as a string
+        let email_verified = match &claims["email_verified"] {
+            Value::Bool(verified) => *verified,
+            Value::String(verified) => verified == "true",
+            _ => false,
+        };
+
+        Ok(OAuthProfile {
+            subject,
+            email: claims["email"].as_str().map(String::from),
+            email_verified,
+            name: claims["name"].as_str().map(String::from),
+        })
+    }
+
+    async fn fetch_github_profile(
+        &self,
+        provider: &OAuthProvider,
+        access_token: &str,
+    ) -> Result<OAuthProfile, ErrorMessage> {
+        let user: GithubUser = self.get_json(&provider.userinfo_url, access_token).await?;
+
+        let emails: Vec<GithubEmail> = self
+            .get_json(&format!("{}/emails", provider.userinfo_url), access_token)
+            .await?;
+
+        let email = emails
+            .into_iter()
+            .find(|email| email.primary && email.verified);
+
+        Ok(OAuthProfile {
+            subject: user.id.to_string(),
+            email_verified: email.is_some(),
+            email: email.map(|email| email.email),
+            name: user.name.or(Some(user.login)),
+        })
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        access_token: &str,
+    ) -> Result<T, ErrorMessage> {
+        self.http
+            .get(url)
+            .bearer_auth(access_token)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|_| ErrorMessage::OAuthProviderError)?
+            .json::<T>()
+            .await
+            .map_err(|_| ErrorMessage::OAuthProviderError)
+    }
+}
+
+// the id token comes straight from the token endpoint over tls, so like the
+// userinfo response it is trusted without checking the signature, only the
+// audience, expiry and the nonce bound to this login attempt are verified
+fn verify_id_token(
+    provider: &OAuthProvider,
+    id_token: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims, ErrorMessage> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.set_audience(&[&provider.client_id]);
+    validation.set_required_spec_claims(&["exp", "aud", "sub"]);
+
+    let claims = decode::<IdTokenClaims>(id_token, &DecodingKey::from_secret(&[]), &validation)
+        .map_err(|_| ErrorMessage::InvalidIdToken)?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(ErrorMessage::InvalidIdToken);
+    }
+
+    Ok(claims)
+}
+
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Json, Router,
+        routing::{get, post},
+    };
+    use chrono::{TimeDelta, Utc};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    const CLIENT_ID: &str = "axum-posts";
+    const NONCE: &str = "expected-nonce";
+
+    // serves the token and userinfo endpoints of an openid connect provider
+    async fn mock_provider(id_claims: Value, userinfo: Value) -> OAuthProvider {
+        let id_token = encode(
+            &Header::default(),
+            &id_claims,
+            &EncodingKey::from_secret(b"provider-secret"),
+        )
+        .unwrap();
+
+        let app = Router::new()
+            .route(
+                "/token",
+                post(async move || {
+                    Json(json!({
+                        "access_token": "provider-access-token",
+                        "token_type": "Bearer",
+                        "id_token": id_token,
+                    }))
+                }),
+            )
+            .route("/userinfo", get(async move || Json(userinfo)));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        OAuthProvider {
+            name: "mock".to_string(),
+            kind: OAuthProviderKind::Oidc,
+            client_id: CLIENT_ID.to_string(),
+            client_secret: "client-secret".to_string(),
+            auth_url: format!("http://{addr}/authorize"),
+            token_url: format!("http://{addr}/token"),
+            userinfo_url: format!("http://{addr}/userinfo"),
+            scopes: "openid email profile".to_string(),
+        }
+    }
+
+    fn client(provider: &OAuthProvider) -> OAuthClient {
+        OAuthClient {
+            http: Client::new(),
+            providers: Arc::new(vec![provider.clone()]),
+            app_url: "http://localhost:8000".to_string(),
+        }
+    }
+
+    fn id_claims(sub: &str, aud: &str, nonce: &str) -> Value {
+        json!({
+            "iss": "http://mock",
+            "sub": sub,
+            "aud": aud,
+            "exp": (Utc::now() + TimeDelta::minutes(5)).timestamp(),
+            "nonce": nonce,
+        })
+    }
+
+    fn userinfo(sub: &str) -> Value {
+        json!({
+            "sub": sub,
+            "email": "ann@example.com",
+            "email_verified": "true",
+            "name": "Ann",
+        })
+    }
+
+    async fn login(provider: &OAuthProvider, nonce: &str) -> Result<OAuthProfile, ErrorMessage> {
+        let client = client(provider);
+        let tokens = client.exchange_code(provider, "code", "verifier").await?;
+        client.fetch_profile(provider, &tokens, nonce).await
+    }
+
+    #[tokio::test]
+    async fn authorize_url_carries_the_state_and_nonce() {
+        let provider = mock_provider(id_claims("42", CLIENT_ID, NONCE), userinfo("42")).await;
+
+        let url = client(&provider)
+            .authorize_url(&provider, "state", "verifier", NONCE)
+            .unwrap();
+        let url = Url::parse(&url).unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        assert_eq!(param("state").as_deref(), Some("state"));
+        assert_eq!(param("nonce").as_deref(), Some(NONCE));
+        assert_eq!(param("code_challenge"), Some(code_challenge("verifier")));
+    }
+
+    #[tokio::test]
+    async fn fetches_the_profile_when_the_nonce_matches() {
+        let provider = mock_provider(id_claims("42", CLIENT_ID, NONCE), userinfo("42")).await;
+
+        let profile = login(&provider, NONCE).await.unwrap();
+
+        assert_eq!(profile.subject, "42");
+        assert_eq!(profile.email.as_deref(), Some("ann@example.com"));
+        assert!(profile.email_verified);
+        assert_eq!(profile.name.as_deref(), Some("Ann"));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_id_token_with_another_nonce() {
+        let provider = mock_provider(id_claims("42", CLIENT_ID, "other"), userinfo("42")).await;
+
+        let result = login(&provider, NONCE).await;
+
+        assert_eq!(result.unwrap_err(), ErrorMessage::InvalidIdToken);
+    }
+
+    #[tokio::test]
+    async fn rejects_an_id_token_for_another_client() {
+        let provider = mock_provider(id_claims("42", "other-client", NONCE), userinfo("42")).await;
+
+        let result = login(&provider, NONCE).await;
+
+        assert_eq!(result.unwrap_err(), ErrorMessage::InvalidIdToken);
+    }
+
+    #[tokio::test]
+    async fn rejects_userinfo_for_another_subject() {
+        let provider = mock_provider(id_claims("42", CLIENT_ID, NONCE), userinfo("43")).await;
+
+        let result = login(&provider, NONCE).await;
+
+        assert_eq!(result.unwrap_err(), ErrorMessage::InvalidIdToken);
+    }
+}
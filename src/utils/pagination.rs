@@ -0,0 +1,369 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    dtos::{post_dto::Post, user_dto::User},
+    error::HttpError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        match self {
+            Self::Asc => Self::Desc,
+            Self::Desc => Self::Asc,
+        }
+    }
+}
+
+// `column` always comes from a whitelist, so it is safe to interpolate into SQL
+#[derive(Debug, Clone, Copy)]
+pub struct Sort {
+    pub column: &'static str,
+    pub direction: SortDirection,
+}
+
+impl Sort {
+    // `sort=field[:asc|:desc]`, defaults to `created_at:desc`
+    pub fn parse(value: Option<&str>, allowed: &[&'static str]) -> Result<Self, HttpError> {
+        let Some(value) = value else {
+            return Ok(Self {
+                column: "created_at",
+                direction: SortDirection::Desc,
+            });
+        };
+
+        let (field, direction) = value.split_once(':').unwrap_or((value, "asc"));
+
+        let column = allowed
+            .iter()
+            .find(|column| **column == field)
+            .ok_or_else(|| {
+                HttpError::bad_request(format!("`sort` must be one of: {}", allowed.join(", ")))
+            })?;
+
+        let direction = match direction {
+            "asc" => SortDirection::Asc,
+            "desc" => SortDirection::Desc,
+            _ => {
+                return Err(HttpError::bad_request(
+                    "`sort` direction must be `asc` or `desc`",
+                ));
+            }
+        };
+
+        Ok(Self { column, direction })
+    }
+
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.column, self.direction.as_sql().to_lowercase())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorDirection {
+    Next,
+    Prev,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeysetValue {
+    Timestamp(DateTime<Utc>),
+    Text(String),
+}
+
+// a cursor points at the row to continue from, using the sort column and `id` as tie-breaker
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    pub sort: String,
+    pub value: KeysetValue,
+    pub id: Uuid,
+    pub direction: CursorDirection,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let direction = match self.direction {
+            CursorDirection::Next => "n",
+            CursorDirection::Prev => "p",
+        };
+        let value = match &self.value {
+            KeysetValue::Timestamp(timestamp) => format!("t{}", timestamp.timestamp_micros()),
+            KeysetValue::Text(text) => format!("s{text}"),
+        };
+
+        URL_SAFE_NO_PAD.encode(format!("{direction}|{}|{}|{value}", self.sort, self.id))
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(value).ok()?).ok()?;
+        let mut parts = decoded.splitn(4, '|');
+
+        let direction = match parts.next()? {
+            "n" => CursorDirection::Next,
+            "p" => CursorDirection::Prev,
+            _ => return None,
+        };
+        let sort = parts.next()?.to_string();
+        let id = Uuid::parse_str(parts.next()?).ok()?;
+        let value = parts.next()?;
+
+        let value = match value.split_at_checked(1)? {
+            ("t", micros) => {
+                KeysetValue::Timestamp(DateTime::from_timestamp_micros(micros.parse().ok()?)?)
+            }
+            ("s", text) => KeysetValue::Text(text.to_string()),
+            _ => return None,
+        };
+
+        Some(Self {
+            sort,
+            value,
+            id,
+            direction,
+        })
+    }
+}
+
+pub trait Keyset {
+    fn keyset(&self, column: &str) -> KeysetValue;
+
+    fn id(&self) -> Uuid;
+}
+
+impl Keyset for Post {
+    fn keyset(&self, column: &str) -> KeysetValue {
+        match column {
+            "updated_at" => KeysetValue::Timestamp(self.updated_at),
+            "title" => KeysetValue::Text(self.title.clone()),
+            _ => KeysetValue::Timestamp(self.created_at),
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Keyset for User {
+    fn keyset(&self, column: &str) -> KeysetValue {
+        match column {
+            "updated_at" => KeysetValue::Timestamp(self.updated_at),
+            "name" => KeysetValue::Text(self.name.clone()),
+            "email" => KeysetValue::Text(self.email.clone()),
+            _ => KeysetValue::Timestamp(self.created_at),
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ListParams {
+    pub sort: Sort,
+    pub cursor: Option<Cursor>,
+    pub page: usize,
+    pub limit: usize,
+}
+
+impl ListParams {
+    pub fn new(
+        page: Option<usize>,
+        limit: Option<usize>,
+        cursor: Option<&str>,
+        sort: Option<&str>,
+        allowed_sort: &[&'static str],
+    ) -> Result<Self, HttpError> {
+        let sort = Sort::parse(sort, allowed_sort)?;
+
+        let cursor = match cursor {
+            Some(_) if page.is_some() => {
+                return Err(HttpError::bad_request(
+                    "`page` and `cursor` cannot be used together",
+                ));
+            }
+            Some(cursor) => Some(
+                Cursor::decode(cursor)
+                    .filter(|cursor| cursor.sort == sort.key())
+                    .ok_or_else(|| {
+                        HttpError::bad_request("Invalid `cursor` param for the requested `sort`")
+                    })?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            sort,
+            cursor,
+            page: page.unwrap_or(1),
+            limit: limit.unwrap_or(10),
+        })
+    }
+
+    // the order rows are fetched in, walking backwards from a `prev` cursor flips it
+    pub fn fetch_direction(&self) -> SortDirection {
+        match &self.cursor {
+            Some(cursor) if cursor.direction == CursorDirection::Prev => {
+                self.sort.direction.reverse()
+            }
+            _ => self.sort.direction,
+        }
+    }
+
+    pub fn offset(&self) -> i64 {
+        offset(self.page, self.limit)
+    }
+
+    pub fn to_page<T: Keyset>(&self, rows: Vec<T>, total: i64) -> Page<T> {
+        match &self.cursor {
+            Some(cursor) => Page::from_cursor(rows, self.limit, cursor, &self.sort),
+            None => Page::from_offset(rows, self.page, self.limit, total, &self.sort),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+impl<T: Keyset> Page<T> {
+    // cursor queries fetch one row more than `limit` to know whether another page follows
+    fn from_cursor(mut rows: Vec<T>, limit: usize, cursor: &Cursor, sort: &Sort) -> Self {
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        match cursor.direction {
+            CursorDirection::Next => Self::new(rows, has_more, true, sort),
+            CursorDirection::Prev => {
+                rows.reverse();
+                Self::new(rows, true, has_more, sort)
+            }
+        }
+    }
+
+    fn from_offset(rows: Vec<T>, page: usize, limit: usize, total: i64, sort: &Sort) -> Self {
+        let has_next = offset(page, limit).saturating_add(rows.len() as i64) < total;
+
+        Self::new(rows, has_next, page > 1, sort)
+    }
+
+    fn new(items: Vec<T>, has_next: bool, has_prev: bool, sort: &Sort) -> Self {
+        let cursor = |item: Option<&T>, direction| {
+            item.map(|item| {
+                Cursor {
+                    sort: sort.key(),
+                    value: item.keyset(sort.column),
+                    id: item.id(),
+                    direction,
+                }
+                .encode()
+            })
+        };
+
+        Self {
+            next_cursor: has_next
+                .then(|| cursor(items.last(), CursorDirection::Next))
+                .flatten(),
+            prev_cursor: has_prev
+                .then(|| cursor(items.first(), CursorDirection::Prev))
+                .flatten(),
+            items,
+        }
+    }
+}
+
+// huge pages clamp to the largest offset postgres accepts instead of wrapping negative
+pub fn offset(page: usize, limit: usize) -> i64 {
+    i64::try_from(page.saturating_sub(1).saturating_mul(limit)).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALLOWED: &[&str] = &["created_at", "updated_at", "title"];
+
+    #[test]
+    fn sort_defaults_to_newest_first() {
+        let sort = Sort::parse(None, ALLOWED).unwrap();
+
+        assert_eq!(sort.column, "created_at");
+        assert_eq!(sort.direction, SortDirection::Desc);
+    }
+
+    #[test]
+    fn sort_direction_defaults_to_ascending() {
+        let sort = Sort::parse(Some("title"), ALLOWED).unwrap();
+        assert_eq!(sort.column, "title");
+        assert_eq!(sort.direction, SortDirection::Asc);
+
+        let sort = Sort::parse(Some("updated_at:desc"), ALLOWED).unwrap();
+        assert_eq!(sort.key(), "updated_at:desc");
+    }
+
+    #[test]
+    fn sort_rejects_unknown_columns_and_directions() {
+        assert!(Sort::parse(Some("password"), ALLOWED).is_err());
+        assert!(Sort::parse(Some("title:sideways"), ALLOWED).is_err());
+        assert!(Sort::parse(Some(""), ALLOWED).is_err());
+    }
+
+    #[test]
+    fn cursor_survives_an_encode_decode_round_trip() {
+        let cursor = Cursor {
+            sort: "title:asc".to_string(),
+            value: KeysetValue::Text("a|title".to_string()),
+            id: Uuid::new_v4(),
+            direction: CursorDirection::Next,
+        };
+
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.sort, cursor.sort);
+        assert_eq!(decoded.value, cursor.value);
+        assert_eq!(decoded.id, cursor.id);
+        assert_eq!(decoded.direction, cursor.direction);
+
+        assert!(Cursor::decode("not a cursor").is_none());
+    }
+
+    #[test]
+    fn cursor_must_match_the_sort() {
+        let cursor = Cursor {
+            sort: "title:asc".to_string(),
+            value: KeysetValue::Text("a".to_string()),
+            id: Uuid::new_v4(),
+            direction: CursorDirection::Next,
+        }
+        .encode();
+
+        assert!(ListParams::new(None, None, Some(&cursor), Some("title"), ALLOWED).is_ok());
+        assert!(ListParams::new(None, None, Some(&cursor), None, ALLOWED).is_err());
+        assert!(ListParams::new(Some(2), None, Some(&cursor), Some("title"), ALLOWED).is_err());
+    }
+
+    #[test]
+    fn offset_clamps_huge_pages() {
+        assert_eq!(offset(1, 10), 0);
+        assert_eq!(offset(0, 10), 0);
+        assert_eq!(offset(4, 25), 75);
+        assert_eq!(offset(usize::MAX, 100), i64::MAX);
+    }
+}
@@ -50,7 +50,7 @@ impl PasswordArgon {
 
         let password_matched = Argon2::default()
             .verify_password(password.as_bytes(), &parsed_hash)
-            .map_or(false, |_| true);
+            .is_ok();
 
         Ok(password_matched)
     }
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use tokio::{spawn, task::JoinHandle, time::interval};
+
+use crate::{
+    db::{DBClient, posts_db::PostExt},
+    utils::config::Env,
+};
+
+pub fn spawn_publish_task(db_client: DBClient, env: &Env) -> JoinHandle<()> {
+    let mut ticker = interval(Duration::from_secs(env.publish_interval.max(1)));
+
+    spawn(async move {
+        loop {
+            ticker.tick().await;
+
+            match db_client.publish_scheduled_posts().await {
+                Ok(0) => {}
+                Ok(count) => println!("Published {count} scheduled posts"),
+                Err(e) => eprintln!("Failed to publish scheduled posts: {e}"),
+            }
+        }
+    })
+}
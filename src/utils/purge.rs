@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use tokio::{spawn, task::JoinHandle, time::interval};
+
+use crate::{
+    db::{DBClient, posts_db::PostExt, users_db::UserExt},
+    utils::config::Env,
+};
+
+pub fn spawn_purge_task(db_client: DBClient, env: &Env) -> JoinHandle<()> {
+    let grace_days = env.account_deletion_grace_days;
+    let retention_days = env.post_trash_retention_days;
+    let mut ticker = interval(Duration::from_secs(env.purge_interval.max(1)));
+
+    spawn(async move {
+        loop {
+            ticker.tick().await;
+
+            match db_client.purge_deactivated_users(grace_days).await {
+                Ok(0) => {}
+                Ok(count) => println!("Purged {count} deactivated users"),
+                Err(e) => eprintln!("Failed to purge deactivated users: {e}"),
+            }
+
+            match db_client.purge_deleted_posts(retention_days).await {
+                Ok(0) => {}
+                Ok(count) => println!("Purged {count} deleted posts"),
+                Err(e) => eprintln!("Failed to purge deleted posts: {e}"),
+            }
+        }
+    })
+}
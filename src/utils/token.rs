@@ -1,60 +1,232 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::http::{HeaderMap, header};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{
-    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode,
-    errors::{Error as JwtError, ErrorKind as JwtErrorKind},
-};
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind as JwtErrorKind};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{dtos::user_dto::User, utils::jwt_keys::JwtKeys};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
+    pub iss: String,
+    pub aud: String,
     pub sub: String,
-    pub exp: i64,
+    pub role: String,
+    #[serde(rename = "type")]
+    pub token_type: TokenType,
+    pub ver: i32,
+    pub jti: String,
     pub iat: i64,
+    pub nbf: i64,
+    pub exp: i64,
 }
 
 impl TokenClaims {
-    pub fn encode(id: &str, secret: &[u8], expires_in_sec: i64) -> Result<String, JwtError> {
-        if id.is_empty() || secret.is_empty() {
-            return Err(JwtErrorKind::InvalidToken.into());
-        }
-
+    pub fn encode(
+        user: &User,
+        token_type: TokenType,
+        keys: &JwtKeys,
+        expires_in_sec: i64,
+    ) -> Result<String, JwtError> {
         let now = Utc::now();
         let iat = now.timestamp();
-        let exp = (now + Duration::minutes(expires_in_sec)).timestamp();
+        let exp = (now + Duration::seconds(expires_in_sec)).timestamp();
         let claims = Self {
-            sub: id.to_string(),
-            exp,
+            iss: keys.issuer().to_string(),
+            aud: keys.audience().to_string(),
+            sub: user.id.to_string(),
+            role: user.role.clone(),
+            token_type,
+            ver: user.token_version,
+            jti: Uuid::new_v4().to_string(),
             iat,
+            nbf: iat,
+            exp,
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(secret),
-        )
-    }
-
-    pub fn decode<T: Into<String>>(token: T, secert: &[u8]) -> Result<Self, JwtError> {
-        match decode::<Self>(
-            &token.into(),
-            &DecodingKey::from_secret(secert),
-            &Validation::new(Algorithm::HS256),
-        ) {
-            Ok(token_data) => Ok(token_data.claims),
-            Err(e) => Err(e),
-        }
+        keys.encode(&claims)
     }
 
-    pub fn validate(self) -> Result<Self, JwtError> {
-        if self.sub.is_empty() {
+    pub fn decode<T: Into<String>>(
+        token: T,
+        token_type: TokenType,
+        keys: &JwtKeys,
+    ) -> Result<Self, JwtError> {
+        let claims: Self = keys.decode(&token.into())?;
+
+        if claims.sub.is_empty() || claims.token_type != token_type {
             return Err(JwtErrorKind::InvalidToken.into());
         }
 
+        Ok(claims)
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn generate_api_key() -> String {
+    format!("axp_{}", generate_token())
+}
+
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<String> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("ApiKey "));
+
+    let api_key = authorization.or_else(|| {
+        headers
+            .get("x-api-key")
+            .and_then(|value| value.to_str().ok())
+    })?;
+
+    Some(api_key.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dtos::user_dto::UserStatus;
+
+    const ISSUER: &str = "axum-posts";
+    const AUDIENCE: &str = "axum-posts-api";
+
+    fn keys() -> JwtKeys {
+        JwtKeys::from_secret(b"test-secret", ISSUER, AUDIENCE)
+    }
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+            password: String::new(),
+            role: "user".to_string(),
+            token_version: 3,
+            email_verified_at: None,
+            totp_secret: None,
+            totp_enabled_at: None,
+            status: UserStatus::Active,
+            suspended_until: None,
+            suspension_reason: None,
+            deleted_at: None,
+            deleted_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn claims(user: &User) -> TokenClaims {
         let now = Utc::now().timestamp();
-        if self.exp < now {
-            return Err(JwtErrorKind::ExpiredSignature.into());
+
+        TokenClaims {
+            iss: ISSUER.to_string(),
+            aud: AUDIENCE.to_string(),
+            sub: user.id.to_string(),
+            role: user.role.clone(),
+            token_type: TokenType::Access,
+            ver: user.token_version,
+            jti: Uuid::new_v4().to_string(),
+            iat: now,
+            nbf: now,
+            exp: now + 60,
         }
+    }
+
+    #[test]
+    fn encoded_claims_decode_back() {
+        let user = user();
+        let token = TokenClaims::encode(&user, TokenType::Access, &keys(), 60).unwrap();
+
+        let claims = TokenClaims::decode(token, TokenType::Access, &keys()).unwrap();
+        assert_eq!(claims.iss, ISSUER);
+        assert_eq!(claims.aud, AUDIENCE);
+        assert_eq!(claims.sub, user.id.to_string());
+        assert_eq!(claims.role, "user");
+        assert_eq!(claims.ver, 3);
+        assert_eq!(claims.nbf, claims.iat);
+        assert_eq!(claims.exp, claims.iat + 60);
+    }
+
+    #[test]
+    fn every_token_gets_its_own_jti() {
+        let user = user();
+        let first = TokenClaims::encode(&user, TokenType::Access, &keys(), 60).unwrap();
+        let second = TokenClaims::encode(&user, TokenType::Access, &keys(), 60).unwrap();
+
+        let first = TokenClaims::decode(first, TokenType::Access, &keys()).unwrap();
+        let second = TokenClaims::decode(second, TokenType::Access, &keys()).unwrap();
+        assert_ne!(first.jti, second.jti);
+    }
+
+    #[test]
+    fn rejects_the_other_token_type() {
+        let token = TokenClaims::encode(&user(), TokenType::Refresh, &keys(), 60).unwrap();
+
+        assert!(TokenClaims::decode(token, TokenType::Access, &keys()).is_err());
+    }
+
+    #[test]
+    fn rejects_another_issuer_or_audience() {
+        let token = TokenClaims::encode(&user(), TokenType::Access, &keys(), 60).unwrap();
+
+        let other_issuer = JwtKeys::from_secret(b"test-secret", "someone-else", AUDIENCE);
+        let other_audience = JwtKeys::from_secret(b"test-secret", ISSUER, "another-api");
+        assert!(TokenClaims::decode(token.clone(), TokenType::Access, &other_issuer).is_err());
+        assert!(TokenClaims::decode(token, TokenType::Access, &other_audience).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_and_not_yet_valid_tokens() {
+        let user = user();
+        let now = Utc::now().timestamp();
+
+        let expired = keys()
+            .encode(&TokenClaims {
+                iat: now - 600,
+                nbf: now - 600,
+                exp: now - 300,
+                ..claims(&user)
+            })
+            .unwrap();
+        let not_yet_valid = keys()
+            .encode(&TokenClaims {
+                nbf: now + 300,
+                exp: now + 600,
+                ..claims(&user)
+            })
+            .unwrap();
+
+        assert!(TokenClaims::decode(expired, TokenType::Access, &keys()).is_err());
+        assert!(TokenClaims::decode(not_yet_valid, TokenType::Access, &keys()).is_err());
+    }
+
+    #[test]
+    fn rejects_an_empty_subject() {
+        let token = keys()
+            .encode(&TokenClaims {
+                sub: String::new(),
+                ..claims(&user())
+            })
+            .unwrap();
 
-        Ok(self)
+        assert!(TokenClaims::decode(token, TokenType::Access, &keys()).is_err());
     }
 }
@@ -0,0 +1,80 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::error::ErrorMessage;
+
+#[derive(Debug, Clone)]
+pub struct Totp;
+
+impl Totp {
+    const ISSUER: &'static str = "Axum Posts";
+    const DIGITS: usize = 6;
+    const SKEW: u8 = 1;
+    const STEP: u64 = 30;
+    const RECOVERY_CODES: usize = 10;
+
+    pub fn generate_secret() -> String {
+        Secret::generate_secret().to_encoded().to_string()
+    }
+
+    pub fn otpauth_uri(secret: &str, account: &str) -> Result<String, ErrorMessage> {
+        Ok(Self::build(secret, account)?.get_url())
+    }
+
+    /// Returns the time step the code was generated for, so callers can
+    /// refuse to accept the same step twice.
+    pub fn verify(secret: &str, code: &str) -> Result<Option<u64>, ErrorMessage> {
+        let code = code.trim();
+
+        if code.len() != Self::DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let mut totp = Self::build(secret, "")?;
+        totp.skew = 0;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| ErrorMessage::ServerError)?
+            .as_secs();
+        let current = now / Self::STEP;
+        let skew = u64::from(Self::SKEW);
+
+        Ok((current.saturating_sub(skew)..=current + skew)
+            .find(|step| totp.check(code, step * Self::STEP)))
+    }
+
+    pub fn generate_recovery_codes() -> Vec<String> {
+        (0..Self::RECOVERY_CODES)
+            .map(|_| {
+                let mut bytes = [0u8; 5];
+                OsRng.fill_bytes(&mut bytes);
+                let code = hex::encode(bytes);
+                format!("{}-{}", &code[..5], &code[5..])
+            })
+            .collect()
+    }
+
+    pub fn normalize_recovery_code(code: &str) -> String {
+        code.trim().to_lowercase()
+    }
+
+    fn build(secret: &str, account: &str) -> Result<TOTP, ErrorMessage> {
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|_| ErrorMessage::InvalidTotpSecret)?;
+
+        TOTP::new(
+            Algorithm::SHA1,
+            Self::DIGITS,
+            Self::SKEW,
+            Self::STEP,
+            secret,
+            Some(Self::ISSUER.to_string()),
+            account.to_string(),
+        )
+        .map_err(|_| ErrorMessage::InvalidTotpSecret)
+    }
+}